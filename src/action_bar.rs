@@ -0,0 +1,171 @@
+// Buttons of the configurable action bar under the channel buttons.
+// Every button runs one or more named actions in order, e.g. `["first-suspicious", "export-csv"]`.
+
+use serde::Deserialize;
+
+use crate::common::ChannelNumber;
+use crate::export::ExportFormat;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BarAction {
+    /// Pulls a fresh sample from the data source.
+    RegenerateData,
+    JumpToFirstSuspicious,
+    Export(ExportFormat),
+    ToggleStreaming,
+    SelectChannel(ChannelNumber),
+    ChangeChannel(i32),
+    ClearCurrent,
+    ClearPrevious,
+    NavigateBack,
+    NavigateForward,
+    AcknowledgeAllEvents,
+    Undo,
+    Redo,
+}
+
+impl BarAction {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (name, argument) = text.trim().split_once(':').unwrap_or((text.trim(), ""));
+        let bad_argument = || format!("'{text}': bad argument");
+        let action = match name {
+            "regenerate-data" => BarAction::RegenerateData,
+            "first-suspicious" => BarAction::JumpToFirstSuspicious,
+            "export-csv" => BarAction::Export(ExportFormat::Csv),
+            "export-json" => BarAction::Export(ExportFormat::Json),
+            "toggle-streaming" => BarAction::ToggleStreaming,
+            "channel" => BarAction::SelectChannel(argument.parse().map_err(|_| bad_argument())?),
+            "change-channel" => {
+                BarAction::ChangeChannel(argument.parse().map_err(|_| bad_argument())?)
+            }
+            "clear-current" => BarAction::ClearCurrent,
+            "clear-previous" => BarAction::ClearPrevious,
+            "back" => BarAction::NavigateBack,
+            "forward" => BarAction::NavigateForward,
+            "acknowledge-all" => BarAction::AcknowledgeAllEvents,
+            "undo" => BarAction::Undo,
+            "redo" => BarAction::Redo,
+            _ => return Err(format!("'{text}': unknown action")),
+        };
+        Ok(action)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActionButton {
+    pub label: String,
+    pub actions: Vec<BarAction>,
+}
+
+/// Button as written in the config file, with either a single `action` or a list of `actions`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ActionButtonConfig {
+    pub label: String,
+    #[serde(default)]
+    pub action: Option<String>,
+    #[serde(default)]
+    pub actions: Vec<String>,
+}
+
+impl ActionButtonConfig {
+    pub fn parse(&self) -> Result<ActionButton, String> {
+        let actions = self
+            .action
+            .iter()
+            .chain(&self.actions)
+            .map(|action| BarAction::parse(action))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|err| format!("button '{}': {err}", self.label))?;
+
+        if actions.is_empty() {
+            return Err(format!("button '{}' has no actions", self.label));
+        }
+
+        Ok(ActionButton {
+            label: self.label.clone(),
+            actions,
+        })
+    }
+}
+
+/// Bar used when the config file doesn't define one.
+pub fn default_action_bar() -> Vec<ActionButton> {
+    vec![
+        ActionButton {
+            label: "Regenerate data".to_string(),
+            actions: vec![BarAction::RegenerateData],
+        },
+        ActionButton {
+            label: "First suspicious".to_string(),
+            actions: vec![BarAction::JumpToFirstSuspicious],
+        },
+        ActionButton {
+            label: "Export snapshot".to_string(),
+            actions: vec![BarAction::Export(ExportFormat::Csv)],
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn button(action: Option<&str>, actions: &[&str]) -> ActionButtonConfig {
+        ActionButtonConfig {
+            label: "Go".to_string(),
+            action: action.map(str::to_string),
+            actions: actions.iter().map(|action| action.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn parsing_actions() {
+        assert_eq!(
+            BarAction::parse(" channel:4 "),
+            Ok(BarAction::SelectChannel(4))
+        );
+        assert_eq!(
+            BarAction::parse("change-channel:-1"),
+            Ok(BarAction::ChangeChannel(-1))
+        );
+        assert_eq!(
+            BarAction::parse("export-json"),
+            Ok(BarAction::Export(ExportFormat::Json))
+        );
+        assert_eq!(
+            BarAction::parse("channel:first"),
+            Err("'channel:first': bad argument".to_string())
+        );
+        assert_eq!(
+            BarAction::parse("self-destruct"),
+            Err("'self-destruct': unknown action".to_string())
+        );
+    }
+
+    #[test]
+    fn buttons_run_action_then_actions() {
+        let button = button(Some("first-suspicious"), &["export-csv", "back"])
+            .parse()
+            .unwrap();
+        assert_eq!(
+            button.actions,
+            [
+                BarAction::JumpToFirstSuspicious,
+                BarAction::Export(ExportFormat::Csv),
+                BarAction::NavigateBack,
+            ]
+        );
+    }
+
+    #[test]
+    fn buttons_without_valid_actions_are_rejected() {
+        assert_eq!(
+            button(None, &[]).parse().unwrap_err(),
+            "button 'Go' has no actions"
+        );
+        assert_eq!(
+            button(Some("back"), &["jump"]).parse().unwrap_err(),
+            "button 'Go': 'jump': unknown action"
+        );
+    }
+}
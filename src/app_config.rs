@@ -0,0 +1,135 @@
+// Per-user configuration of the retained-mode app, read once on startup.
+// Unlike the session file it's written by hand, so every section is optional.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use crate::action_bar::{self, ActionButton, ActionButtonConfig};
+use crate::common::APP_CONFIG_DIR_NAME;
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const CONFIG_FILE_FLAG: &str = "--config";
+pub const CONFIG_FILE_ENV: &str = "CHANNEL_APP_CONFIG";
+pub const CONFIG_FILE_NAME: &str = "config.json";
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub action_bar: Option<Vec<ActionButtonConfig>>,
+}
+
+impl AppConfig {
+    /// Reads the config from `path`, an empty config if there is no file or it can't be used.
+    pub fn load(path: &Path) -> Self {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return AppConfig::default(),
+            Err(err) => {
+                eprintln!("Can't read config file {}: {err}", path.display());
+                return AppConfig::default();
+            }
+        };
+
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            eprintln!("Broken config file {}: {err}", path.display());
+            AppConfig::default()
+        })
+    }
+
+    /// Configured action bar, or the default one if it's missing or has invalid buttons.
+    pub fn action_bar(&self) -> Vec<ActionButton> {
+        let Some(buttons) = &self.action_bar else {
+            return action_bar::default_action_bar();
+        };
+
+        buttons
+            .iter()
+            .map(ActionButtonConfig::parse)
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_or_else(|err| {
+                eprintln!("Invalid action bar in the config file, {err}. Using the default one.");
+                action_bar::default_action_bar()
+            })
+    }
+}
+
+/// Config file from `--config`/`CHANNEL_APP_CONFIG`, or the default one in the user's
+/// config directory.
+pub fn config_file_path() -> Option<PathBuf> {
+    startup_option(CONFIG_FILE_FLAG, CONFIG_FILE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| {
+            dirs::config_dir().map(|dir| dir.join(APP_CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+        })
+}
+
+/// Config from the file given at startup or the default location.
+pub fn from_startup_args() -> AppConfig {
+    config_file_path()
+        .map(|path| AppConfig::load(&path))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::action_bar::BarAction;
+    use crate::test_util::TempDir;
+
+    fn labels(buttons: &[ActionButton]) -> Vec<&str> {
+        buttons.iter().map(|button| button.label.as_str()).collect()
+    }
+
+    fn action_bar_from(contents: &str) -> Vec<ActionButton> {
+        let temp_dir = TempDir::new("config");
+        let path = temp_dir.file(CONFIG_FILE_NAME);
+        fs::write(&path, contents).unwrap();
+        AppConfig::load(&path).action_bar()
+    }
+
+    #[test]
+    fn configured_action_bar() {
+        let buttons = action_bar_from(
+            r#"{ "action_bar": [
+                { "label": "Back", "action": "back" },
+                { "label": "Export", "actions": ["first-suspicious", "export-json"] }
+            ] }"#,
+        );
+        assert_eq!(labels(&buttons), ["Back", "Export"]);
+        assert_eq!(buttons[0].actions, [BarAction::NavigateBack]);
+        assert_eq!(buttons[1].actions.len(), 2);
+
+        assert!(action_bar_from(r#"{ "action_bar": [] }"#).is_empty());
+    }
+
+    #[test]
+    fn unusable_configs_give_the_default_action_bar() {
+        let default_labels = labels(&action_bar::default_action_bar())
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+
+        let temp_dir = TempDir::new("missing_config");
+        let missing = AppConfig::load(&temp_dir.file(CONFIG_FILE_NAME)).action_bar();
+        assert_eq!(labels(&missing), default_labels);
+
+        for contents in [
+            "{}",
+            r#"{ "action_bar": [ { "label": "Oops", "action": "jump" } ] }"#,
+            r#"{ "action_bar": [ { "label": "Nothing" } ] }"#,
+            r#"{ "action_bar": [ { "action": "back" } ] }"#,
+            "{ not json",
+        ] {
+            assert_eq!(
+                labels(&action_bar_from(contents)),
+                default_labels,
+                "{contents}"
+            );
+        }
+    }
+}
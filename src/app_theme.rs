@@ -0,0 +1,344 @@
+// Colour themes shared by both front ends.
+// The theme is stored as a short spec (`dark`, `custom:background=#202225,text=#e6e6e6`) so
+// the same startup option and theme file work for iced and egui alike.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::common::APP_CONFIG_DIR_NAME;
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const THEME_FLAG: &str = "--theme";
+pub const THEME_ENV: &str = "CHANNEL_THEME";
+pub const THEME_FILE_FLAG: &str = "--theme-file";
+pub const THEME_FILE_ENV: &str = "CHANNEL_THEME_FILE";
+pub const THEME_FILE_NAME: &str = "theme.txt";
+
+pub type Rgb = [u8; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Palette {
+    pub background: Rgb,
+    pub text: Rgb,
+    /// Accent of selected and primary controls.
+    pub primary: Rgb,
+    /// Status colour of channels that look normal.
+    pub normal: Rgb,
+    /// Status colour of suspicious channels.
+    pub suspicious: Rgb,
+}
+
+pub const LIGHT_PALETTE: Palette = Palette {
+    background: [0xff, 0xff, 0xff],
+    text: [0x00, 0x00, 0x00],
+    primary: [0x5e, 0x7c, 0xe2],
+    normal: [0x12, 0x66, 0x4f],
+    suspicious: [0xc3, 0x42, 0x3f],
+};
+
+pub const DARK_PALETTE: Palette = Palette {
+    background: [0x20, 0x22, 0x25],
+    text: [0xe6, 0xe6, 0xe6],
+    primary: [0x5e, 0x7c, 0xe2],
+    normal: [0x3c, 0xb3, 0x71],
+    suspicious: [0xe0, 0x5a, 0x55],
+};
+
+pub const HIGH_CONTRAST_PALETTE: Palette = Palette {
+    background: [0x00, 0x00, 0x00],
+    text: [0xff, 0xff, 0xff],
+    primary: [0xff, 0xd7, 0x00],
+    normal: [0x00, 0xff, 0x7f],
+    suspicious: [0xff, 0x30, 0x30],
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PaletteColor {
+    Background,
+    Text,
+    Primary,
+    Normal,
+    Suspicious,
+}
+
+impl PaletteColor {
+    pub const ALL: [PaletteColor; 5] = [
+        PaletteColor::Background,
+        PaletteColor::Text,
+        PaletteColor::Primary,
+        PaletteColor::Normal,
+        PaletteColor::Suspicious,
+    ];
+
+    /// Name used in custom theme specs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            PaletteColor::Background => "background",
+            PaletteColor::Text => "text",
+            PaletteColor::Primary => "primary",
+            PaletteColor::Normal => "normal",
+            PaletteColor::Suspicious => "suspicious",
+        }
+    }
+}
+
+impl Palette {
+    pub fn color(&self, which: PaletteColor) -> Rgb {
+        match which {
+            PaletteColor::Background => self.background,
+            PaletteColor::Text => self.text,
+            PaletteColor::Primary => self.primary,
+            PaletteColor::Normal => self.normal,
+            PaletteColor::Suspicious => self.suspicious,
+        }
+    }
+
+    pub fn set_color(&mut self, which: PaletteColor, rgb: Rgb) {
+        match which {
+            PaletteColor::Background => self.background = rgb,
+            PaletteColor::Text => self.text = rgb,
+            PaletteColor::Primary => self.primary = rgb,
+            PaletteColor::Normal => self.normal = rgb,
+            PaletteColor::Suspicious => self.suspicious = rgb,
+        }
+    }
+
+    #[cfg(feature = "retained-mode")]
+    pub fn status_color(&self, is_suspicious: bool) -> Rgb {
+        if is_suspicious {
+            self.suspicious
+        } else {
+            self.normal
+        }
+    }
+
+    /// Whether the background is dark, egui picks its base visuals from it.
+    #[cfg(feature = "immediate-mode")]
+    pub fn is_dark(&self) -> bool {
+        let [red, green, blue] = self.background.map(u32::from);
+        (red * 299 + green * 587 + blue * 114) / 1000 < 128
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ThemeChoice {
+    #[default]
+    Light,
+    Dark,
+    HighContrast,
+    Custom(Palette),
+}
+
+impl ThemeChoice {
+    /// Choices offered in the settings, `custom` is the palette used for `Custom`.
+    pub fn all(custom: Palette) -> [ThemeChoice; 4] {
+        [
+            ThemeChoice::Light,
+            ThemeChoice::Dark,
+            ThemeChoice::HighContrast,
+            ThemeChoice::Custom(custom),
+        ]
+    }
+
+    pub fn palette(&self) -> Palette {
+        match self {
+            ThemeChoice::Light => LIGHT_PALETTE,
+            ThemeChoice::Dark => DARK_PALETTE,
+            ThemeChoice::HighContrast => HIGH_CONTRAST_PALETTE,
+            ThemeChoice::Custom(palette) => *palette,
+        }
+    }
+
+    /// Parses `light`, `dark`, `high-contrast` or `custom:<color>=#rrggbb,...`.
+    /// Colours missing from a custom spec are taken from the dark theme, the other themes
+    /// take no colours.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (kind, colors) = spec.trim().split_once(':').unwrap_or((spec.trim(), ""));
+        match kind {
+            "light" if colors.is_empty() => Some(ThemeChoice::Light),
+            "dark" if colors.is_empty() => Some(ThemeChoice::Dark),
+            "high-contrast" if colors.is_empty() => Some(ThemeChoice::HighContrast),
+            "custom" => {
+                let mut palette = DARK_PALETTE;
+                for entry in colors.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                    let (name, hex) = entry.split_once('=')?;
+                    let which = PaletteColor::ALL
+                        .into_iter()
+                        .find(|which| which.name() == name.trim())?;
+                    palette.set_color(which, parse_hex_color(hex)?);
+                }
+                Some(ThemeChoice::Custom(palette))
+            }
+            _ => None,
+        }
+    }
+
+    pub fn spec(&self) -> String {
+        match self {
+            ThemeChoice::Light => "light".to_string(),
+            ThemeChoice::Dark => "dark".to_string(),
+            ThemeChoice::HighContrast => "high-contrast".to_string(),
+            ThemeChoice::Custom(palette) => {
+                let colors: Vec<_> = PaletteColor::ALL
+                    .iter()
+                    .map(|which| format!("{}={}", which.name(), to_hex(palette.color(*which))))
+                    .collect();
+                format!("custom:{}", colors.join(","))
+            }
+        }
+    }
+
+    /// Reads the theme saved to `path`, light if there is no file or it can't be used.
+    pub fn load(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(spec) => ThemeChoice::parse(&spec).unwrap_or_else(|| {
+                eprintln!(
+                    "Broken theme file {}, using the light theme.",
+                    path.display()
+                );
+                ThemeChoice::default()
+            }),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => ThemeChoice::default(),
+            Err(err) => {
+                eprintln!("Can't read theme file {}: {err}", path.display());
+                ThemeChoice::default()
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, self.spec())
+    }
+}
+
+impl fmt::Display for ThemeChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ThemeChoice::Light => "Light",
+            ThemeChoice::Dark => "Dark",
+            ThemeChoice::HighContrast => "High contrast",
+            ThemeChoice::Custom(_) => "Custom",
+        })
+    }
+}
+
+/// "#ff8800" or "ff8800"
+pub fn parse_hex_color(text: &str) -> Option<Rgb> {
+    let hex = text.trim().trim_start_matches('#');
+    if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let channel = |position: usize| u8::from_str_radix(&hex[position..position + 2], 16).ok();
+    Some([channel(0)?, channel(2)?, channel(4)?])
+}
+
+pub fn to_hex([red, green, blue]: Rgb) -> String {
+    format!("#{red:02x}{green:02x}{blue:02x}")
+}
+
+/// Theme file from `--theme-file`/`CHANNEL_THEME_FILE`, or the default one in the user's
+/// config directory, shared by both front ends.
+pub fn theme_file_path() -> Option<PathBuf> {
+    startup_option(THEME_FILE_FLAG, THEME_FILE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| {
+            dirs::config_dir().map(|dir| dir.join(APP_CONFIG_DIR_NAME).join(THEME_FILE_NAME))
+        })
+}
+
+/// Theme from `--theme` or `CHANNEL_THEME`, then the saved theme file, light otherwise.
+pub fn from_startup_args() -> ThemeChoice {
+    if let Some(spec) = startup_option(THEME_FLAG, THEME_ENV) {
+        match ThemeChoice::parse(&spec) {
+            Some(choice) => return choice,
+            None => eprintln!("Unknown theme '{spec}', using the saved one."),
+        }
+    }
+
+    theme_file_path()
+        .map(|path| ThemeChoice::load(&path))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn parsing_theme_specs() {
+        assert_eq!(ThemeChoice::parse(" dark\n"), Some(ThemeChoice::Dark));
+        assert_eq!(
+            ThemeChoice::parse("high-contrast"),
+            Some(ThemeChoice::HighContrast)
+        );
+        for bad_spec in ["", "solarized", "Dark", "light:text=#000000"] {
+            assert_eq!(ThemeChoice::parse(bad_spec), None, "{bad_spec}");
+        }
+    }
+
+    #[test]
+    fn custom_themes_fill_missing_colors_from_the_dark_theme() {
+        let Some(ThemeChoice::Custom(palette)) =
+            ThemeChoice::parse("custom: text = #010203 ,suspicious=ff0000,")
+        else {
+            panic!("custom theme expected");
+        };
+        assert_eq!(
+            palette,
+            Palette {
+                text: [0x01, 0x02, 0x03],
+                suspicious: [0xff, 0x00, 0x00],
+                ..DARK_PALETTE
+            }
+        );
+        assert_eq!(
+            ThemeChoice::parse("custom:"),
+            Some(ThemeChoice::Custom(DARK_PALETTE))
+        );
+
+        let choice = ThemeChoice::Custom(palette);
+        assert_eq!(ThemeChoice::parse(&choice.spec()), Some(choice));
+    }
+
+    #[test]
+    fn bad_custom_colors_are_rejected() {
+        for bad_spec in [
+            "custom:text",
+            "custom:shadow=#000000",
+            "custom:text=#00000",
+            "custom:text=#0000000",
+            "custom:text=#00000g",
+            "custom:text=#+f+f+f",
+            "custom:text=#ééé",
+        ] {
+            assert_eq!(ThemeChoice::parse(bad_spec), None, "{bad_spec}");
+        }
+        assert_eq!(parse_hex_color("#5E7CE2"), Some([0x5e, 0x7c, 0xe2]));
+        assert_eq!(to_hex([0x5e, 0x7c, 0xe2]), "#5e7ce2");
+    }
+
+    #[test]
+    fn saved_themes_load_back() {
+        let temp_dir = TempDir::new("theme");
+        let path = temp_dir.file("nested").join(THEME_FILE_NAME);
+        assert_eq!(ThemeChoice::load(&path), ThemeChoice::Light);
+
+        let choice = ThemeChoice::Custom(HIGH_CONTRAST_PALETTE);
+        choice.save(&path).unwrap();
+        assert_eq!(ThemeChoice::load(&path), choice);
+
+        fs::write(&path, "custom:text=#zzzzzz").unwrap();
+        assert_eq!(ThemeChoice::load(&path), ThemeChoice::Light);
+    }
+}
@@ -0,0 +1,392 @@
+// Sources of channel values for the retained-mode app.
+// The UI only talks to `ChannelDataSource`, so values can come from a generator, a file,
+// a recorded replay or a local socket without touching the view code.
+
+use std::fs;
+use std::io::{self, BufRead, BufReader};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+
+use crate::channel_value::{ChannelValue, ValueKind, FLOAT_DECIMALS};
+use crate::startup_args::{parsed_startup_option, startup_option};
+
+//*  Constants */
+pub const DATA_SOURCE_FLAG: &str = "--source";
+pub const DATA_SOURCE_ENV: &str = "CHANNEL_DATA_SOURCE";
+pub const SAMPLE_INTERVAL_FLAG: &str = "--sample-interval-ms";
+pub const SAMPLE_INTERVAL_ENV: &str = "CHANNEL_SAMPLE_INTERVAL_MS";
+pub const DEFAULT_SAMPLE_INTERVAL_MS: u32 = 1000;
+pub const SEED_FLAG: &str = "--seed";
+pub const SEED_ENV: &str = "CHANNEL_SEED";
+/// Time allowed for connecting to a socket source and for each of its frames to arrive.
+pub const SOCKET_TIMEOUT_MS: u64 = 2000;
+
+/// Sources are read off the UI thread, so they have to be `Send`.
+pub trait ChannelDataSource: Send {
+    /// Human-readable description of where the values come from.
+    fn describe(&self) -> String;
+
+    /// Returns the next value for each of `channels_count` channels.
+    fn next_values(&mut self, channels_count: usize) -> io::Result<Vec<ChannelValue>>;
+
+    /// Seed of generated values, `None` for sources that don't generate anything.
+    fn seed(&self) -> Option<u64> {
+        None
+    }
+}
+
+/// Uniformly distributed values within the range of `kind`, the original behaviour of the app.
+/// The same seed gives the same sequence of values with the same build of the app.
+pub struct RandomDataSource {
+    kind: ValueKind,
+    seed: u64,
+    rng: StdRng,
+}
+
+impl RandomDataSource {
+    pub fn new(kind: ValueKind, seed: u64) -> Self {
+        RandomDataSource {
+            kind,
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn next_value(&mut self) -> ChannelValue {
+        let range = self.kind.range();
+        let (start, end) = (*range.start(), *range.end());
+        // Rounded so generated values look like values typed in by hand
+        let scale = 10f64.powi(FLOAT_DECIMALS as i32);
+        let mut float = || (self.rng.gen_range(start..=end) * scale).round() / scale;
+        match &self.kind {
+            ValueKind::Unsigned => {
+                ChannelValue::Unsigned(self.rng.gen_range(start as u32..=end as u32))
+            }
+            ValueKind::Signed => {
+                ChannelValue::Signed(self.rng.gen_range(start as i64..=end as i64))
+            }
+            ValueKind::Float => ChannelValue::Float(float()),
+            ValueKind::Fields(names) => {
+                ChannelValue::Fields((0..names.len()).map(|_| float()).collect())
+            }
+        }
+    }
+}
+
+impl ChannelDataSource for RandomDataSource {
+    fn describe(&self) -> String {
+        let range = self.kind.range();
+        format!(
+            "random {} {}..={} (seed {})",
+            self.kind,
+            range.start(),
+            range.end(),
+            self.seed
+        )
+    }
+
+    fn next_values(&mut self, channels_count: usize) -> io::Result<Vec<ChannelValue>> {
+        Ok((0..channels_count).map(|_| self.next_value()).collect())
+    }
+
+    fn seed(&self) -> Option<u64> {
+        Some(self.seed)
+    }
+}
+
+/// A single snapshot of values read from a text file, returned on every request.
+pub struct FileDataSource {
+    path: PathBuf,
+    values: Vec<ChannelValue>,
+}
+
+impl FileDataSource {
+    pub fn open(path: impl Into<PathBuf>, kind: &ValueKind) -> io::Result<Self> {
+        let path = path.into();
+        let values = parse_frame(&fs::read_to_string(&path)?, kind)?;
+        Ok(FileDataSource { path, values })
+    }
+}
+
+impl ChannelDataSource for FileDataSource {
+    fn describe(&self) -> String {
+        format!("file {}", self.path.display())
+    }
+
+    fn next_values(&mut self, channels_count: usize) -> io::Result<Vec<ChannelValue>> {
+        Ok(take_channels(self.values.clone(), channels_count))
+    }
+}
+
+/// Recorded frames, one per line, played back in order and wrapped around at the end.
+pub struct ReplayDataSource {
+    path: PathBuf,
+    frames: Vec<Vec<ChannelValue>>,
+    next_frame: usize,
+}
+
+impl ReplayDataSource {
+    pub fn open(path: impl Into<PathBuf>, kind: &ValueKind) -> io::Result<Self> {
+        let path = path.into();
+        let frames = fs::read_to_string(&path)?
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| parse_frame(line, kind))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        if frames.is_empty() {
+            return Err(invalid_data("replay file has no frames"));
+        }
+
+        Ok(ReplayDataSource {
+            path,
+            frames,
+            next_frame: 0,
+        })
+    }
+}
+
+impl ChannelDataSource for ReplayDataSource {
+    fn describe(&self) -> String {
+        format!("replay {}", self.path.display())
+    }
+
+    fn next_values(&mut self, channels_count: usize) -> io::Result<Vec<ChannelValue>> {
+        let frame = self.frames[self.next_frame].clone();
+        self.next_frame = (self.next_frame + 1) % self.frames.len();
+        Ok(take_channels(frame, channels_count))
+    }
+}
+
+/// Reads one line-delimited frame per request from a local TCP socket.
+pub struct SocketDataSource {
+    address: String,
+    reader: BufReader<TcpStream>,
+    kind: ValueKind,
+}
+
+impl SocketDataSource {
+    pub fn connect(address: &str, kind: &ValueKind) -> io::Result<Self> {
+        let timeout = Duration::from_millis(SOCKET_TIMEOUT_MS);
+        // `connect_timeout` takes a single address, so try each one the address resolves to
+        let mut last_error = io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("'{address}' doesn't resolve to any address"),
+        );
+        let mut stream = None;
+        for socket_address in address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&socket_address, timeout) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(err) => last_error = err,
+            }
+        }
+        let stream = stream.ok_or(last_error)?;
+        stream.set_read_timeout(Some(timeout))?;
+        Ok(SocketDataSource {
+            address: address.to_string(),
+            reader: BufReader::new(stream),
+            kind: kind.clone(),
+        })
+    }
+}
+
+impl ChannelDataSource for SocketDataSource {
+    fn describe(&self) -> String {
+        format!("socket {}", self.address)
+    }
+
+    fn next_values(&mut self, channels_count: usize) -> io::Result<Vec<ChannelValue>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "socket closed by the peer",
+            ));
+        }
+        let frame = parse_frame(&line, &self.kind)?;
+        Ok(take_channels(frame, channels_count))
+    }
+}
+
+/// Creates the data source requested at startup.
+///
+/// The source is taken from `--source <spec>` (or `--source=<spec>`) on the command line,
+/// then from the `CHANNEL_DATA_SOURCE` environment variable. Supported specs are
+/// `random`, `file:<path>`, `replay:<path>` and `socket:<address>`.
+/// Falls back to random values if nothing is set or the requested source can't be opened.
+/// Random values are seeded by `seed_from_startup_args`, every source reads or generates
+/// values of `value_kind`.
+pub fn from_startup_args(value_kind: &ValueKind) -> Box<dyn ChannelDataSource> {
+    let seed = seed_from_startup_args();
+    let Some(spec) = startup_option(DATA_SOURCE_FLAG, DATA_SOURCE_ENV) else {
+        return Box::new(RandomDataSource::new(value_kind.clone(), seed));
+    };
+
+    match from_spec(&spec, value_kind, seed) {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("Can't use data source '{spec}': {err}. Falling back to random values.");
+            Box::new(RandomDataSource::new(value_kind.clone(), seed))
+        }
+    }
+}
+
+pub fn from_spec(
+    spec: &str,
+    value_kind: &ValueKind,
+    seed: u64,
+) -> io::Result<Box<dyn ChannelDataSource>> {
+    let (kind, argument) = spec.split_once(':').unwrap_or((spec, ""));
+    match kind {
+        "random" => Ok(Box::new(RandomDataSource::new(value_kind.clone(), seed))),
+        "file" => Ok(Box::new(FileDataSource::open(argument, value_kind)?)),
+        "replay" => Ok(Box::new(ReplayDataSource::open(argument, value_kind)?)),
+        "socket" => Ok(Box::new(SocketDataSource::connect(argument, value_kind)?)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("unknown data source kind '{kind}'"),
+        )),
+    }
+}
+
+/// Seed from `--seed` or `CHANNEL_SEED`, a fresh random one when neither is set so every
+/// session can still be rerun with the seed shown in the About tab.
+pub fn seed_from_startup_args() -> u64 {
+    parsed_startup_option::<u64>(SEED_FLAG, SEED_ENV).unwrap_or_else(|| thread_rng().gen())
+}
+
+/// Interval between streamed samples, from `--sample-interval-ms` or `CHANNEL_SAMPLE_INTERVAL_MS`.
+pub fn sample_interval_from_startup_args() -> Duration {
+    let millis = parsed_startup_option::<u32>(SAMPLE_INTERVAL_FLAG, SAMPLE_INTERVAL_ENV)
+        .filter(|&millis| millis > 0)
+        .unwrap_or(DEFAULT_SAMPLE_INTERVAL_MS);
+    Duration::from_millis(millis as u64)
+}
+
+// Values may be separated by commas and/or whitespace, fields of one value by `;`.
+// Values outside the range of `kind` are rejected like values typed in by the user.
+fn parse_frame(line: &str, kind: &ValueKind) -> io::Result<Vec<ChannelValue>> {
+    line.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .map(|token| {
+            kind.parse_in_range(token, kind.range())
+                .map_err(|err| invalid_data(&format!("bad value '{token}': {err}")))
+        })
+        .collect()
+}
+
+// Sources may have fewer values than there are channels, the rest keep their last value
+// instead of failing every sample
+fn take_channels(mut values: Vec<ChannelValue>, channels_count: usize) -> Vec<ChannelValue> {
+    values.truncate(channels_count);
+    values
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::TempDir;
+
+    fn fields() -> ValueKind {
+        ValueKind::Fields(vec!["x".to_string(), "y".to_string()])
+    }
+
+    #[test]
+    fn frames_accept_commas_and_whitespace() {
+        assert_eq!(
+            parse_frame(" 1, 2\t3 ,,4\n", &ValueKind::Unsigned).unwrap(),
+            [1, 2, 3, 4].map(ChannelValue::Unsigned)
+        );
+        assert_eq!(
+            parse_frame("1.5;2 0;10", &fields()).unwrap(),
+            [
+                ChannelValue::Fields(vec![1.5, 2.0]),
+                ChannelValue::Fields(vec![0.0, 10.0])
+            ]
+        );
+        assert!(parse_frame("", &ValueKind::Signed).unwrap().is_empty());
+    }
+
+    #[test]
+    fn frames_reject_bad_values() {
+        for (line, kind) in [
+            ("1 abc", ValueKind::Unsigned),
+            ("1 101", ValueKind::Unsigned),
+            ("0", ValueKind::Unsigned),
+            ("-51", ValueKind::Signed),
+            ("10.5", ValueKind::Float),
+            ("1;2;3", fields()),
+            ("1;11", fields()),
+        ] {
+            let err = parse_frame(line, &kind).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{line}");
+            assert!(err.to_string().starts_with("bad value"), "{line}: {err}");
+        }
+    }
+
+    #[test]
+    fn take_channels_fits_frames_to_the_channels() {
+        let values = [1, 2, 3].map(ChannelValue::Unsigned).to_vec();
+        assert_eq!(take_channels(values.clone(), 2), values[..2]);
+        assert_eq!(take_channels(values.clone(), 3), values);
+        assert_eq!(take_channels(values.clone(), 5), values);
+        assert_eq!(take_channels(Vec::new(), 1), []);
+    }
+
+    #[test]
+    fn sources_from_spec() {
+        let mut source = from_spec("random", &ValueKind::Signed, 7).unwrap();
+        assert_eq!(source.seed(), Some(7));
+        let values = source.next_values(5).unwrap();
+        assert_eq!(values.len(), 5);
+        assert!(values
+            .iter()
+            .all(|value| ValueKind::Signed.range().contains(&value.scalar())));
+        let mut same_seed = from_spec("random", &ValueKind::Signed, 7).unwrap();
+        assert_eq!(same_seed.next_values(5).unwrap(), values);
+
+        let err = from_spec("carrier-pigeon:home", &ValueKind::Unsigned, 0).err();
+        assert_eq!(err.map(|err| err.kind()), Some(io::ErrorKind::InvalidInput));
+        assert!(from_spec("file:", &ValueKind::Unsigned, 0).is_err());
+    }
+
+    #[test]
+    fn file_and_replay_specs_read_their_files() {
+        let temp_dir = TempDir::new("data_source");
+        let path = temp_dir.file("frames.txt");
+        fs::write(&path, "1 2 3\n\n4 5 6\n").unwrap();
+
+        let mut file =
+            from_spec(&format!("file:{}", path.display()), &ValueKind::Unsigned, 0).unwrap();
+        assert_eq!(file.seed(), None);
+        assert_eq!(
+            file.next_values(2).unwrap(),
+            [1, 2].map(ChannelValue::Unsigned)
+        );
+
+        let mut replay = from_spec(
+            &format!("replay:{}", path.display()),
+            &ValueKind::Unsigned,
+            0,
+        )
+        .unwrap();
+        let frames: Vec<_> = (0..3).map(|_| replay.next_values(4).unwrap()).collect();
+        assert_eq!(
+            frames,
+            [[1, 2, 3], [4, 5, 6], [1, 2, 3]]
+                .map(|frame| frame.map(ChannelValue::Unsigned).to_vec())
+        );
+    }
+}
@@ -0,0 +1,178 @@
+// Bounded per-channel history of timestamped samples.
+
+use std::collections::VecDeque;
+use std::ops::RangeInclusive;
+use std::time::{Duration, SystemTime};
+
+use crate::channel_value::ChannelValue;
+use crate::startup_args::parsed_startup_option;
+
+//*  Constants */
+pub const HISTORY_CAPACITY_FLAG: &str = "--history-len";
+pub const HISTORY_CAPACITY_ENV: &str = "CHANNEL_HISTORY_LEN";
+pub const DEFAULT_HISTORY_CAPACITY: usize = 32;
+pub const SPARKLINE_WIDTH: usize = 16;
+
+const SPARKLINE_BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+#[derive(Debug, Clone)]
+pub struct ChannelSample {
+    pub timestamp: SystemTime,
+    pub value: ChannelValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Up,
+    Down,
+    Flat,
+}
+
+impl Trend {
+    pub fn as_symbol(&self) -> &'static str {
+        match self {
+            Trend::Up => "↑",
+            Trend::Down => "↓",
+            Trend::Flat => "→",
+        }
+    }
+}
+
+/// Ring buffer keeping the latest `capacity` samples of one channel.
+#[derive(Debug, Clone)]
+pub struct ChannelHistory {
+    samples: VecDeque<ChannelSample>,
+    capacity: usize,
+}
+
+impl Default for ChannelHistory {
+    fn default() -> Self {
+        ChannelHistory::with_capacity(DEFAULT_HISTORY_CAPACITY)
+    }
+}
+
+impl ChannelHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        ChannelHistory {
+            samples: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: ChannelValue) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(ChannelSample {
+            timestamp: SystemTime::now(),
+            value,
+        });
+    }
+
+    pub fn samples(&self) -> impl Iterator<Item = &ChannelSample> {
+        self.samples.iter()
+    }
+
+    /// Scalars of the buffered values, oldest first.
+    pub fn values(&self) -> Vec<f64> {
+        self.samples
+            .iter()
+            .map(|sample| sample.value.scalar())
+            .collect()
+    }
+
+    /// Time covered by the buffered samples.
+    pub fn span(&self) -> Duration {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(first), Some(last)) => last
+                .timestamp
+                .duration_since(first.timestamp)
+                .unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+
+    /// Direction of the last change, `Flat` until there are at least two samples.
+    pub fn trend(&self) -> Trend {
+        let mut latest = self.samples.iter().rev();
+        match (latest.next(), latest.next()) {
+            (Some(last), Some(before)) if last.value.scalar() > before.value.scalar() => Trend::Up,
+            (Some(last), Some(before)) if last.value.scalar() < before.value.scalar() => {
+                Trend::Down
+            }
+            _ => Trend::Flat,
+        }
+    }
+
+    /// Renders the last `width` samples as unicode bars scaled to `low..=high`.
+    pub fn sparkline(&self, width: usize, range: RangeInclusive<f64>) -> String {
+        let (low, high) = (*range.start(), *range.end());
+        let span = (high - low).max(f64::EPSILON);
+        let top_bar = (SPARKLINE_BARS.len() - 1) as f64;
+        let skipped = self.samples.len().saturating_sub(width);
+
+        self.samples
+            .iter()
+            .skip(skipped)
+            .map(|sample| {
+                let ratio = (sample.value.scalar().clamp(low, high) - low) / span;
+                SPARKLINE_BARS[(ratio * top_bar).round() as usize]
+            })
+            .collect()
+    }
+}
+
+/// History capacity from `--history-len` or `CHANNEL_HISTORY_LEN`.
+pub fn capacity_from_startup_args() -> usize {
+    parsed_startup_option::<usize>(HISTORY_CAPACITY_FLAG, HISTORY_CAPACITY_ENV)
+        .filter(|&capacity| capacity > 0)
+        .unwrap_or(DEFAULT_HISTORY_CAPACITY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn history_of(capacity: usize, values: &[u32]) -> ChannelHistory {
+        let mut history = ChannelHistory::with_capacity(capacity);
+        for &value in values {
+            history.push(ChannelValue::Unsigned(value));
+        }
+        history
+    }
+
+    #[test]
+    fn oldest_samples_make_room_at_capacity() {
+        assert_eq!(history_of(3, &[1, 2]).values(), [1.0, 2.0]);
+        assert_eq!(history_of(3, &[1, 2, 3]).values(), [1.0, 2.0, 3.0]);
+        assert_eq!(history_of(3, &[1, 2, 3, 4, 5]).values(), [3.0, 4.0, 5.0]);
+        assert_eq!(history_of(3, &[1, 2, 3, 4, 5]).samples().count(), 3);
+    }
+
+    #[test]
+    fn capacity_is_at_least_one() {
+        assert_eq!(history_of(0, &[1, 2]).values(), [2.0]);
+        assert_eq!(history_of(1, &[1, 2]).values(), [2.0]);
+        assert_eq!(ChannelHistory::default().capacity, DEFAULT_HISTORY_CAPACITY);
+    }
+
+    #[test]
+    fn trend_follows_the_last_change() {
+        assert_eq!(history_of(4, &[]).trend(), Trend::Flat);
+        assert_eq!(history_of(4, &[5]).trend(), Trend::Flat);
+        assert_eq!(history_of(4, &[9, 1, 2]).trend(), Trend::Up);
+        assert_eq!(history_of(4, &[1, 9, 2]).trend(), Trend::Down);
+        assert_eq!(history_of(4, &[1, 2, 2]).trend(), Trend::Flat);
+        // Wrapping around doesn't mix up old and new samples
+        assert_eq!(history_of(2, &[1, 9, 5, 3]).trend(), Trend::Down);
+    }
+
+    #[test]
+    fn sparklines_show_the_latest_samples_in_range() {
+        let history = history_of(8, &[100, 0, 1, 50, 100, 200]);
+        assert_eq!(history.sparkline(4, 1.0..=100.0), "▁▄██");
+        assert_eq!(history.sparkline(16, 1.0..=100.0).chars().count(), 6);
+        assert_eq!(history.sparkline(0, 1.0..=100.0), "");
+    }
+}
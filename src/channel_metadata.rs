@@ -0,0 +1,91 @@
+// User-editable description of a channel: name, unit, colour and position in lists.
+// Everything is optional, a channel without metadata is shown by its number as before.
+
+use serde::{Deserialize, Serialize};
+
+use crate::app_theme::{self, Rgb};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MetadataField {
+    Name,
+    Unit,
+    Description,
+    Color,
+    DisplayOrder,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChannelMetadata {
+    pub name: String,
+    pub unit: String,
+    pub description: String,
+    /// `#rrggbb`, empty for the default colour.
+    pub color: String,
+    /// Position in channel lists, the channel number when not set.
+    pub display_order: Option<u32>,
+}
+
+impl ChannelMetadata {
+    /// "3" for unnamed channels, "3 Pressure" for named ones.
+    pub fn label(&self, channel_index: usize) -> String {
+        if self.name.is_empty() {
+            (channel_index + 1).to_string()
+        } else {
+            format!("{} {}", channel_index + 1, self.name)
+        }
+    }
+
+    /// Multi-line description for tooltips.
+    pub fn details(&self, channel_index: usize) -> String {
+        let mut details = format!("Channel {}", self.label(channel_index));
+        if !self.unit.is_empty() {
+            details.push_str(&format!("\nUnit: {}", self.unit));
+        }
+        if !self.description.is_empty() {
+            details.push_str(&format!("\n{}", self.description));
+        }
+        details
+    }
+
+    pub fn sort_key(&self, channel_index: usize) -> (u32, usize) {
+        (
+            self.display_order.unwrap_or(channel_index as u32 + 1),
+            channel_index,
+        )
+    }
+
+    pub fn rgb(&self) -> Option<Rgb> {
+        app_theme::parse_hex_color(&self.color)
+    }
+
+    pub fn field_text(&self, field: MetadataField) -> String {
+        match field {
+            MetadataField::Name => self.name.clone(),
+            MetadataField::Unit => self.unit.clone(),
+            MetadataField::Description => self.description.clone(),
+            MetadataField::Color => self.color.clone(),
+            MetadataField::DisplayOrder => self
+                .display_order
+                .map(|order| order.to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Applies text typed into the editor. A display order that isn't a number is ignored
+    /// so typing can't leave the field in a broken state, an empty one resets it.
+    pub fn set_field(&mut self, field: MetadataField, text: String) {
+        match field {
+            MetadataField::Name => self.name = text,
+            MetadataField::Unit => self.unit = text,
+            MetadataField::Description => self.description = text,
+            MetadataField::Color => self.color = text,
+            MetadataField::DisplayOrder if text.trim().is_empty() => self.display_order = None,
+            MetadataField::DisplayOrder => {
+                if let Ok(order) = text.trim().parse() {
+                    self.display_order = Some(order);
+                }
+            }
+        }
+    }
+}
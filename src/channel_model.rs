@@ -0,0 +1,871 @@
+// Channel state and the messages that change it, independent of any UI framework.
+// The iced app forwards its messages here and only keeps what is about presentation,
+// so channel selection, navigation and suspicious evaluation can be tested without a display.
+
+use serde::{Deserialize, Serialize};
+
+use crate::channel_history::ChannelHistory;
+use crate::channel_metadata::{ChannelMetadata, MetadataField};
+use crate::channel_value::{ChannelValue, ValueKind};
+use crate::common::ChannelNumber;
+use crate::event_log::{EventLog, Transition};
+use crate::navigation::NavigationHistory;
+use crate::suspicious_rules::{RuleContext, RuleSet, SuspiciousRule};
+use crate::undo_history::UndoHistory;
+
+//*  Constants */
+pub const DEFAULT_CHANNELS_COUNT: usize = 9;
+pub const MIN_CHANNELS_COUNT: usize = 1;
+pub const MAX_CHANNELS_COUNT: usize = 256;
+pub const BACKUP_CHANNEL_INDEX: usize = 0;
+
+#[derive(Debug, Clone)]
+pub struct ChannelInfo {
+    pub value: ChannelValue,
+    /// Rule that flagged the channel as suspicious, `None` while the value looks normal.
+    pub suspicious_rule: Option<SuspiciousRule>,
+    /// Every rule that fired on the last evaluation, hysteresis rules latch on it.
+    pub fired_rules: Vec<SuspiciousRule>,
+    pub history: ChannelHistory,
+    pub metadata: ChannelMetadata,
+    /// Lowest and highest values since the session started, `None` before the first value.
+    pub running_min: Option<f64>,
+    pub running_max: Option<f64>,
+    /// Set by a manual override, samples leave the value alone until it's cleared.
+    pub is_overridden: bool,
+}
+
+impl ChannelInfo {
+    pub fn new(initial_value: ChannelValue, history_capacity: usize) -> Self {
+        ChannelInfo {
+            value: initial_value,
+            suspicious_rule: None,
+            fired_rules: Vec::new(),
+            history: ChannelHistory::with_capacity(history_capacity),
+            metadata: ChannelMetadata::default(),
+            running_min: None,
+            running_max: None,
+            is_overridden: false,
+        }
+    }
+
+    pub fn is_suspicious(&self) -> bool {
+        self.suspicious_rule.is_some()
+    }
+
+    pub fn record_value(&mut self, value: ChannelValue) {
+        let scalar = value.scalar();
+        self.running_min = Some(self.running_min.map_or(scalar, |min| min.min(scalar)));
+        self.running_max = Some(self.running_max.map_or(scalar, |max| max.max(scalar)));
+        self.history.push(value.clone());
+        self.value = value;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChannelDataRow {
+    Previous,
+    Current,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChannelMessage {
+    /// Anything outside the channel set is ignored.
+    SelectChannel(ChannelNumber),
+    /// Moves the selection by `change` channels, wrapping around at both ends.
+    ChangeChannel(i32),
+    ClearRow(ChannelDataRow),
+    NavigateBack,
+    NavigateForward,
+    JumpToNavigationEntry(usize),
+    ModifyingChannelsCount(u32),
+    ReleasedChannelsCountSlider,
+    ModifyingSuspiciousLimit(f64),
+    ReleasedSuspiciousSlider,
+    /// One value per channel, from the first one on. Overridden channels keep their value.
+    SampleReceived(Vec<ChannelValue>),
+    /// `(channel index, value)` pairs, indices start from 0. Overridden channels keep their
+    /// value as with samples.
+    ValuesImported(Vec<(usize, ChannelValue)>),
+    JumpToFirstSuspicious,
+    AcknowledgeEvent(u64),
+    AcknowledgeAllEvents,
+    ClearAcknowledgedEvents,
+    /// Text typed into a metadata field of the channel with the given index (from 0).
+    EditMetadata(usize, MetadataField, String),
+    /// Manual value for the channel with the given index (from 0), checked by the caller.
+    OverrideValue(usize, ChannelValue),
+    ClearOverride(usize),
+    Undo,
+    Redo,
+}
+
+impl ChannelMessage {
+    /// Messages whose effect can be undone. A whole drag of the suspicious limit slider is
+    /// undone at once, so the slider messages are handled separately.
+    fn is_undoable(&self) -> bool {
+        matches!(
+            self,
+            ChannelMessage::SelectChannel(_)
+                | ChannelMessage::ChangeChannel(_)
+                | ChannelMessage::ClearRow(_)
+                | ChannelMessage::NavigateBack
+                | ChannelMessage::NavigateForward
+                | ChannelMessage::JumpToNavigationEntry(_)
+                | ChannelMessage::JumpToFirstSuspicious
+                | ChannelMessage::ValuesImported(_)
+        )
+    }
+}
+
+/// What undo and redo restore: the selection, the suspicious limit and, for imports, the values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoSnapshot {
+    navigation: NavigationHistory,
+    suspicious_limit: f64,
+    values: Option<Vec<ChannelValue>>,
+}
+
+#[derive(Debug)]
+pub struct ChannelModel {
+    pub navigation: NavigationHistory,
+    pub channel_data: Vec<ChannelInfo>,
+    /// Channel count shown while the slider is dragged, applied once it's released.
+    pub requested_channels_count: usize,
+    pub history_capacity: usize,
+    /// Value of channels before their first sample, the lowest value of the range.
+    initial_value: ChannelValue,
+    pub current_suspicious_limit: f64,
+    pub rules: RuleSet,
+    pub event_log: EventLog,
+    undo_history: UndoHistory<UndoSnapshot>,
+    /// State from before the suspicious limit slider started moving.
+    limit_before_drag: Option<UndoSnapshot>,
+}
+
+impl ChannelModel {
+    pub fn new(
+        channels_count: usize,
+        history_capacity: usize,
+        value_kind: &ValueKind,
+        suspicious_limit: f64,
+        rules: RuleSet,
+    ) -> Self {
+        let channels_count = channels_count.clamp(MIN_CHANNELS_COUNT, MAX_CHANNELS_COUNT);
+        let initial_value = value_kind.default_value();
+        ChannelModel {
+            navigation: NavigationHistory::default(),
+            channel_data: (0..channels_count)
+                .map(|_| ChannelInfo::new(initial_value.clone(), history_capacity))
+                .collect(),
+            requested_channels_count: channels_count,
+            history_capacity,
+            initial_value,
+            current_suspicious_limit: suspicious_limit,
+            rules,
+            event_log: EventLog::default(),
+            undo_history: UndoHistory::default(),
+            limit_before_drag: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        self.undo_history.can_undo()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        self.undo_history.can_redo()
+    }
+
+    fn snapshot(&self, with_values: bool) -> UndoSnapshot {
+        UndoSnapshot {
+            navigation: self.navigation.clone(),
+            suspicious_limit: self.current_suspicious_limit,
+            values: with_values.then(|| {
+                self.channel_data
+                    .iter()
+                    .map(|channel_info| channel_info.value.clone())
+                    .collect()
+            }),
+        }
+    }
+
+    // The channel count may have changed since the snapshot, so selections and values
+    // are fitted to the current channels. Restored values aren't new samples, so history and
+    // running min/max stay as they are.
+    fn restore(&mut self, snapshot: UndoSnapshot) {
+        self.navigation = snapshot.navigation;
+        self.navigation.retain_channels(self.channel_data.len());
+        self.current_suspicious_limit = snapshot.suspicious_limit;
+        for (channel_info, value) in self
+            .channel_data
+            .iter_mut()
+            .zip(snapshot.values.into_iter().flatten())
+        {
+            channel_info.value = value;
+        }
+        self.update_suspicious();
+    }
+
+    fn undo(&mut self) {
+        let mut undo_history = std::mem::take(&mut self.undo_history);
+        if let Some(snapshot) =
+            undo_history.undo(|restored| self.snapshot(restored.values.is_some()))
+        {
+            self.restore(snapshot);
+        }
+        self.undo_history = undo_history;
+    }
+
+    fn redo(&mut self) {
+        let mut undo_history = std::mem::take(&mut self.undo_history);
+        if let Some(snapshot) =
+            undo_history.redo(|restored| self.snapshot(restored.values.is_some()))
+        {
+            self.restore(snapshot);
+        }
+        self.undo_history = undo_history;
+    }
+
+    pub fn channels_count(&self) -> usize {
+        self.channel_data.len()
+    }
+
+    fn select(&mut self, channel: usize) {
+        if (1..=self.channel_data.len()).contains(&channel) {
+            self.navigation.visit(channel - 1);
+        }
+    }
+
+    fn change_channel(&mut self, change: i32) {
+        let Some(current_channel_index) = self.navigation.current() else {
+            self.navigation.visit(BACKUP_CHANNEL_INDEX);
+            return;
+        };
+
+        let channels_count = self.channel_data.len() as i64;
+        let new_channel_index =
+            (current_channel_index as i64 + change as i64).rem_euclid(channels_count) as usize;
+        self.navigation.visit(new_channel_index);
+    }
+
+    // Grows or shrinks the channel set, dropping selections that point past its end.
+    // New channels start at 0 until the next sample arrives.
+    fn resize_channels(&mut self, new_count: usize) {
+        let new_count = new_count.clamp(MIN_CHANNELS_COUNT, MAX_CHANNELS_COUNT);
+        self.requested_channels_count = new_count;
+        if new_count == self.channel_data.len() {
+            return;
+        }
+
+        let history_capacity = self.history_capacity;
+        let initial_value = &self.initial_value;
+        self.channel_data.resize_with(new_count, || {
+            ChannelInfo::new(initial_value.clone(), history_capacity)
+        });
+        self.navigation.retain_channels(new_count);
+    }
+
+    fn set_values(&mut self, values: impl IntoIterator<Item = (usize, ChannelValue)>) {
+        for (index, value) in values {
+            if let Some(channel_info) = self.channel_data.get_mut(index) {
+                channel_info.record_value(value);
+            }
+        }
+        self.update_suspicious();
+    }
+
+    // Samples and imports leave manually overridden channels alone
+    fn set_source_values(&mut self, values: impl IntoIterator<Item = (usize, ChannelValue)>) {
+        let values: Vec<_> = values
+            .into_iter()
+            .filter(|&(index, _)| !self.is_overridden(index))
+            .collect();
+        self.set_values(values);
+    }
+
+    pub fn is_overridden(&self, index: usize) -> bool {
+        self.channel_data
+            .get(index)
+            .is_some_and(|channel_info| channel_info.is_overridden)
+    }
+
+    fn update_suspicious(&mut self) {
+        for (index, data) in self.channel_data.iter_mut().enumerate() {
+            let mut recent_values = data.history.values();
+            // The rules judge the value on screen, which undo, redo and overrides change
+            // without a sample
+            match recent_values.last_mut() {
+                Some(last) => *last = data.value.scalar(),
+                None => recent_values.push(data.value.scalar()),
+            }
+            let context = RuleContext {
+                recent_values: &recent_values,
+                current_limit: self.current_suspicious_limit,
+                previously_fired: &data.fired_rules,
+            };
+            let previous_rule = data.suspicious_rule.take();
+            data.fired_rules = self.rules.evaluate(index, &context);
+            data.suspicious_rule = data.fired_rules.first().cloned();
+
+            // Logged with the limit of the rule that fired, or of the one that stopped firing
+            let limit = self.current_suspicious_limit;
+            let (rule, transition) = match (&data.suspicious_rule, &previous_rule) {
+                (Some(rule), None) => (rule, Transition::BecameSuspicious(rule.explain(limit))),
+                (None, Some(rule)) => (rule, Transition::BecameNormal),
+                _ => continue,
+            };
+            self.event_log.record(
+                index,
+                data.value.clone(),
+                rule.limit(data.value.scalar(), limit),
+                transition,
+            );
+        }
+    }
+}
+
+/// Applies `message` to `model`. Doesn't touch anything outside the model, so the same
+/// sequence of messages always leads to the same selection and values.
+///
+/// Undoable messages that actually changed something are recorded for undo.
+pub fn update(model: &mut ChannelModel, message: ChannelMessage) {
+    let before = match &message {
+        ChannelMessage::ModifyingSuspiciousLimit(_) => {
+            if model.limit_before_drag.is_none() {
+                model.limit_before_drag = Some(model.snapshot(false));
+            }
+            None
+        }
+        ChannelMessage::ReleasedSuspiciousSlider => model.limit_before_drag.take(),
+        message if message.is_undoable() => {
+            Some(model.snapshot(matches!(message, ChannelMessage::ValuesImported(_))))
+        }
+        _ => None,
+    };
+
+    apply(model, message);
+
+    if let Some(before) = before {
+        if before != model.snapshot(before.values.is_some()) {
+            model.undo_history.record(before);
+        }
+    }
+}
+
+fn apply(model: &mut ChannelModel, message: ChannelMessage) {
+    match message {
+        ChannelMessage::SelectChannel(channel) => model.select(channel),
+        ChannelMessage::ChangeChannel(change) => model.change_channel(change),
+        ChannelMessage::ClearRow(row) => match row {
+            ChannelDataRow::Previous => model.navigation.remove_previous(),
+            ChannelDataRow::Current => model.navigation.remove_current(),
+        },
+        ChannelMessage::NavigateBack => model.navigation.back(),
+        ChannelMessage::NavigateForward => model.navigation.forward(),
+        ChannelMessage::JumpToNavigationEntry(position) => model.navigation.jump_to(position),
+        ChannelMessage::ModifyingChannelsCount(new_count) => {
+            model.requested_channels_count = new_count as usize;
+        }
+        ChannelMessage::ReleasedChannelsCountSlider => {
+            model.resize_channels(model.requested_channels_count);
+        }
+        ChannelMessage::ModifyingSuspiciousLimit(new_limit) => {
+            model.current_suspicious_limit = new_limit;
+        }
+        ChannelMessage::ReleasedSuspiciousSlider => model.update_suspicious(),
+        ChannelMessage::SampleReceived(values) => {
+            model.set_source_values(values.into_iter().enumerate());
+        }
+        ChannelMessage::ValuesImported(values) => model.set_source_values(values),
+        ChannelMessage::JumpToFirstSuspicious => {
+            if let Some(index) = model
+                .channel_data
+                .iter()
+                .position(|channel_info| channel_info.is_suspicious())
+            {
+                model.navigation.visit(index);
+            }
+        }
+        ChannelMessage::AcknowledgeEvent(id) => model.event_log.acknowledge(id),
+        ChannelMessage::AcknowledgeAllEvents => model.event_log.acknowledge_all(),
+        ChannelMessage::ClearAcknowledgedEvents => model.event_log.clear_acknowledged(),
+        ChannelMessage::EditMetadata(index, field, text) => {
+            if let Some(channel_info) = model.channel_data.get_mut(index) {
+                channel_info.metadata.set_field(field, text);
+            }
+        }
+        // A manual value isn't a sample, so history and running min/max stay as they are
+        ChannelMessage::OverrideValue(index, value) => {
+            if let Some(channel_info) = model.channel_data.get_mut(index) {
+                channel_info.is_overridden = true;
+                channel_info.value = value;
+                model.update_suspicious();
+            }
+        }
+        ChannelMessage::ClearOverride(index) => {
+            if let Some(channel_info) = model.channel_data.get_mut(index) {
+                channel_info.is_overridden = false;
+            }
+        }
+        ChannelMessage::Undo => model.undo(),
+        ChannelMessage::Redo => model.redo(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn model_with_channels(channels_count: usize) -> ChannelModel {
+        ChannelModel::new(
+            channels_count,
+            8,
+            &ValueKind::Unsigned,
+            75.0,
+            RuleSet::default(),
+        )
+    }
+
+    fn sample(values: &[u32]) -> ChannelMessage {
+        ChannelMessage::SampleReceived(values.iter().copied().map(ChannelValue::Unsigned).collect())
+    }
+
+    fn imported(values: &[(usize, u32)]) -> ChannelMessage {
+        ChannelMessage::ValuesImported(
+            values
+                .iter()
+                .map(|&(index, value)| (index, ChannelValue::Unsigned(value)))
+                .collect(),
+        )
+    }
+
+    fn values(model: &ChannelModel) -> Vec<f64> {
+        model
+            .channel_data
+            .iter()
+            .map(|channel_info| channel_info.value.scalar())
+            .collect()
+    }
+
+    fn selection(model: &ChannelModel) -> (Option<usize>, Option<usize>) {
+        (model.navigation.previous(), model.navigation.current())
+    }
+
+    #[test]
+    fn nothing_is_selected_initially() {
+        let model = model_with_channels(9);
+        assert_eq!(selection(&model), (None, None));
+    }
+
+    #[test]
+    fn selecting_moves_current_to_previous() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(3));
+        update(&mut model, ChannelMessage::SelectChannel(5));
+        assert_eq!(selection(&model), (Some(2), Some(4)));
+    }
+
+    #[test]
+    fn selecting_invalid_channels_is_ignored() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(2));
+        update(&mut model, ChannelMessage::SelectChannel(0));
+        update(&mut model, ChannelMessage::SelectChannel(10));
+        update(&mut model, ChannelMessage::SelectChannel(usize::MAX));
+        assert_eq!(selection(&model), (None, Some(1)));
+    }
+
+    #[test]
+    fn change_channel_wraps_around() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(9));
+        update(&mut model, ChannelMessage::ChangeChannel(1));
+        assert_eq!(model.navigation.current(), Some(0));
+
+        update(&mut model, ChannelMessage::ChangeChannel(-1));
+        assert_eq!(model.navigation.current(), Some(8));
+
+        update(&mut model, ChannelMessage::ChangeChannel(-20));
+        assert_eq!(model.navigation.current(), Some(6));
+    }
+
+    #[test]
+    fn change_channel_without_selection_selects_backup_channel() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::ChangeChannel(3));
+        assert_eq!(model.navigation.current(), Some(BACKUP_CHANNEL_INDEX));
+    }
+
+    #[test]
+    fn clearing_rows() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(1));
+        update(&mut model, ChannelMessage::SelectChannel(2));
+        update(&mut model, ChannelMessage::SelectChannel(3));
+
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Previous),
+        );
+        assert_eq!(selection(&model), (Some(0), Some(2)));
+
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Current),
+        );
+        assert_eq!(selection(&model), (Some(0), None));
+
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Previous),
+        );
+        assert_eq!(selection(&model), (None, None));
+
+        // Clearing empty rows does nothing
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Current),
+        );
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Previous),
+        );
+        assert_eq!(selection(&model), (None, None));
+    }
+
+    #[test]
+    fn shrinking_drops_selection_past_the_end() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(2));
+        update(&mut model, ChannelMessage::SelectChannel(8));
+        update(&mut model, ChannelMessage::ModifyingChannelsCount(4));
+        update(&mut model, ChannelMessage::ReleasedChannelsCountSlider);
+
+        assert_eq!(model.channels_count(), 4);
+        assert_eq!(selection(&model), (None, Some(1)));
+    }
+
+    #[test]
+    fn channels_count_is_clamped() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::ModifyingChannelsCount(0));
+        update(&mut model, ChannelMessage::ReleasedChannelsCountSlider);
+        assert_eq!(model.channels_count(), MIN_CHANNELS_COUNT);
+
+        update(&mut model, ChannelMessage::ModifyingChannelsCount(10_000));
+        update(&mut model, ChannelMessage::ReleasedChannelsCountSlider);
+        assert_eq!(model.channels_count(), MAX_CHANNELS_COUNT);
+    }
+
+    #[test]
+    fn new_channels_start_at_the_lowest_value_of_their_kind() {
+        let mut model = ChannelModel::new(1, 8, &ValueKind::Float, 7.5, RuleSet::default());
+        assert_eq!(model.channel_data[0].value, ChannelValue::Float(0.0));
+
+        update(&mut model, ChannelMessage::ModifyingChannelsCount(2));
+        update(&mut model, ChannelMessage::ReleasedChannelsCountSlider);
+        assert_eq!(model.channel_data[1].value, ChannelValue::Float(0.0));
+        assert!(!model.channel_data[1].is_suspicious());
+    }
+
+    #[test]
+    fn short_samples_leave_the_other_channels_alone() {
+        let mut model = model_with_channels(3);
+        update(&mut model, sample(&[10, 80, 90]));
+        update(&mut model, sample(&[20]));
+
+        assert_eq!(values(&model), [20.0, 80.0, 90.0]);
+        assert_eq!(model.channel_data[1].history.values(), [80.0]);
+        assert_eq!(model.event_log.unacknowledged_count(), 2);
+    }
+
+    #[test]
+    fn samples_update_suspicious_state_and_log() {
+        let mut model = model_with_channels(3);
+        update(&mut model, sample(&[10, 80, 90]));
+        assert!(!model.channel_data[0].is_suspicious());
+        assert!(model.channel_data[1].is_suspicious());
+        assert_eq!(model.event_log.unacknowledged_count(), 2);
+
+        update(&mut model, ChannelMessage::JumpToFirstSuspicious);
+        assert_eq!(model.navigation.current(), Some(1));
+
+        update(&mut model, ChannelMessage::ModifyingSuspiciousLimit(85.0));
+        update(&mut model, ChannelMessage::ReleasedSuspiciousSlider);
+        assert!(!model.channel_data[1].is_suspicious());
+        assert!(model.channel_data[2].is_suspicious());
+    }
+
+    #[test]
+    fn events_name_the_rule_that_fired() {
+        let rules = RuleSet::parse("limit; 2: upper 20").unwrap();
+        let mut model = ChannelModel::new(2, 8, &ValueKind::Unsigned, 75.0, rules);
+        update(&mut model, sample(&[80, 30]));
+        update(&mut model, sample(&[10, 10]));
+
+        let events: Vec<_> = model
+            .event_log
+            .events()
+            .map(|event| (event.channel_index, event.limit, event.transition.clone()))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                (0, 75.0, Transition::BecameSuspicious("> 75".to_string())),
+                (1, 20.0, Transition::BecameSuspicious("> 20".to_string())),
+                (0, 75.0, Transition::BecameNormal),
+                (1, 20.0, Transition::BecameNormal),
+            ]
+        );
+    }
+
+    #[test]
+    fn imported_values_outside_the_channel_set_are_ignored() {
+        let mut model = model_with_channels(3);
+        update(&mut model, imported(&[(1, 42), (7, 99)]));
+        assert_eq!(values(&model), [1.0, 42.0, 1.0]);
+    }
+
+    #[test]
+    fn running_min_and_max_cover_all_samples() {
+        let mut model = model_with_channels(2);
+        update(&mut model, sample(&[50, 10]));
+        update(&mut model, sample(&[20, 90]));
+        update(&mut model, sample(&[30, 40]));
+
+        let ranges: Vec<_> = model
+            .channel_data
+            .iter()
+            .map(|channel_info| (channel_info.running_min, channel_info.running_max))
+            .collect();
+        assert_eq!(ranges, [(Some(20.0), Some(50.0)), (Some(10.0), Some(90.0))]);
+    }
+
+    #[test]
+    fn editing_metadata() {
+        let mut model = model_with_channels(3);
+        update(
+            &mut model,
+            ChannelMessage::EditMetadata(1, MetadataField::Name, "Pressure".to_string()),
+        );
+        update(
+            &mut model,
+            ChannelMessage::EditMetadata(1, MetadataField::DisplayOrder, "0".to_string()),
+        );
+        update(
+            &mut model,
+            ChannelMessage::EditMetadata(1, MetadataField::DisplayOrder, "x".to_string()),
+        );
+        update(
+            &mut model,
+            ChannelMessage::EditMetadata(5, MetadataField::Name, "Nowhere".to_string()),
+        );
+
+        let metadata = &model.channel_data[1].metadata;
+        assert_eq!(metadata.label(1), "2 Pressure");
+        assert_eq!(metadata.display_order, Some(0));
+        assert!(metadata.sort_key(1) < model.channel_data[0].metadata.sort_key(0));
+    }
+
+    #[test]
+    fn undo_and_redo_selection_and_clearing() {
+        let mut model = model_with_channels(9);
+        update(&mut model, ChannelMessage::SelectChannel(2));
+        update(&mut model, ChannelMessage::SelectChannel(4));
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Current),
+        );
+        assert_eq!(selection(&model), (Some(1), None));
+
+        update(&mut model, ChannelMessage::Undo);
+        assert_eq!(selection(&model), (Some(1), Some(3)));
+        update(&mut model, ChannelMessage::Undo);
+        update(&mut model, ChannelMessage::Undo);
+        assert_eq!(selection(&model), (None, None));
+        assert!(!model.can_undo());
+
+        update(&mut model, ChannelMessage::Redo);
+        update(&mut model, ChannelMessage::Redo);
+        assert_eq!(selection(&model), (Some(1), Some(3)));
+
+        // A new change drops what could still be redone
+        update(&mut model, ChannelMessage::SelectChannel(7));
+        assert!(!model.can_redo());
+    }
+
+    #[test]
+    fn no_ops_are_not_recorded() {
+        let mut model = model_with_channels(9);
+        update(
+            &mut model,
+            ChannelMessage::ClearRow(ChannelDataRow::Current),
+        );
+        update(&mut model, ChannelMessage::SelectChannel(0));
+        update(&mut model, ChannelMessage::NavigateBack);
+        update(&mut model, sample(&[1; 9]));
+        assert!(!model.can_undo());
+
+        update(&mut model, ChannelMessage::SelectChannel(3));
+        update(&mut model, ChannelMessage::SelectChannel(3));
+        update(&mut model, ChannelMessage::Undo);
+        assert_eq!(selection(&model), (None, None));
+    }
+
+    #[test]
+    fn slider_drag_is_undone_at_once() {
+        let mut model = model_with_channels(3);
+        update(&mut model, sample(&[10, 60, 90]));
+        for limit in [70.0, 60.0, 50.0] {
+            update(&mut model, ChannelMessage::ModifyingSuspiciousLimit(limit));
+        }
+        update(&mut model, ChannelMessage::ReleasedSuspiciousSlider);
+        assert!(model.channel_data[1].is_suspicious());
+
+        update(&mut model, ChannelMessage::Undo);
+        assert_eq!(model.current_suspicious_limit, 75.0);
+        assert!(!model.channel_data[1].is_suspicious());
+        assert!(!model.can_undo());
+    }
+
+    #[test]
+    fn undo_import_restores_values() {
+        let mut model = model_with_channels(3);
+        update(&mut model, sample(&[10, 20, 30]));
+        update(&mut model, imported(&[(0, 99), (2, 1)]));
+        update(&mut model, ChannelMessage::Undo);
+        assert_eq!(values(&model), [10.0, 20.0, 30.0]);
+        assert!(!model.channel_data[0].is_suspicious());
+
+        update(&mut model, ChannelMessage::Redo);
+        assert_eq!(model.channel_data[0].value, ChannelValue::Unsigned(99));
+        assert!(model.channel_data[0].is_suspicious());
+    }
+
+    #[test]
+    fn undo_and_redo_leave_history_and_statistics_alone() {
+        let mut model = model_with_channels(2);
+        update(&mut model, sample(&[10, 20]));
+        update(&mut model, imported(&[(0, 99)]));
+        update(&mut model, ChannelMessage::Undo);
+        update(&mut model, ChannelMessage::Redo);
+        update(&mut model, ChannelMessage::Undo);
+
+        let channel_info = &model.channel_data[0];
+        assert_eq!(channel_info.value, ChannelValue::Unsigned(10));
+        assert_eq!(channel_info.history.values(), [10.0, 99.0]);
+        assert_eq!(
+            (channel_info.running_min, channel_info.running_max),
+            (Some(10.0), Some(99.0))
+        );
+        assert_eq!(model.channel_data[1].history.values(), [20.0]);
+        assert!(!channel_info.is_suspicious());
+    }
+
+    fn any_message() -> impl Strategy<Value = ChannelMessage> {
+        prop_oneof![
+            (0usize..20).prop_map(ChannelMessage::SelectChannel),
+            (-30i32..30).prop_map(ChannelMessage::ChangeChannel),
+            Just(ChannelMessage::ClearRow(ChannelDataRow::Previous)),
+            Just(ChannelMessage::ClearRow(ChannelDataRow::Current)),
+            Just(ChannelMessage::NavigateBack),
+            Just(ChannelMessage::NavigateForward),
+            (0usize..20).prop_map(ChannelMessage::JumpToNavigationEntry),
+            (0u32..20).prop_map(ChannelMessage::ModifyingChannelsCount),
+            Just(ChannelMessage::ReleasedChannelsCountSlider),
+            (1u32..=100).prop_map(|limit| ChannelMessage::ModifyingSuspiciousLimit(limit as f64)),
+            Just(ChannelMessage::ReleasedSuspiciousSlider),
+            prop::collection::vec(1u32..=100, 0..20).prop_map(|values| sample(&values)),
+            Just(ChannelMessage::JumpToFirstSuspicious),
+        ]
+    }
+
+    #[test]
+    fn overridden_values_survive_samples_until_cleared() {
+        let mut model = model_with_channels(3);
+        update(
+            &mut model,
+            ChannelMessage::OverrideValue(1, ChannelValue::Unsigned(42)),
+        );
+        update(&mut model, sample(&[1, 2, 3]));
+        assert_eq!(values(&model), [1.0, 42.0, 3.0]);
+        assert!(model.channel_data[1].is_overridden);
+
+        update(&mut model, imported(&[(0, 7), (1, 8)]));
+        assert_eq!(values(&model), [7.0, 42.0, 3.0]);
+        assert!(model.channel_data[1].is_overridden);
+
+        update(&mut model, ChannelMessage::ClearOverride(1));
+        update(&mut model, sample(&[4, 5, 6]));
+        assert_eq!(model.channel_data[1].value, ChannelValue::Unsigned(5));
+        assert!(!model.channel_data[1].is_overridden);
+    }
+
+    #[test]
+    fn overrides_leave_history_and_statistics_alone() {
+        let mut model = model_with_channels(2);
+        update(&mut model, sample(&[10]));
+        for index in 0..2 {
+            update(
+                &mut model,
+                ChannelMessage::OverrideValue(index, ChannelValue::Unsigned(90)),
+            );
+        }
+
+        let channel_info = &model.channel_data[0];
+        assert!(channel_info.is_suspicious());
+        assert_eq!(channel_info.history.values(), [10.0]);
+        assert_eq!(
+            (channel_info.running_min, channel_info.running_max),
+            (Some(10.0), Some(10.0))
+        );
+        // Judged without any sample yet
+        assert!(model.channel_data[1].is_suspicious());
+        assert!(model.channel_data[1].history.values().is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn selection_always_points_at_existing_channels(
+            messages in prop::collection::vec(any_message(), 0..64)
+        ) {
+            let mut model = model_with_channels(9);
+            for message in messages {
+                update(&mut model, message);
+                let channels_count = model.channels_count();
+                prop_assert!((MIN_CHANNELS_COUNT..=MAX_CHANNELS_COUNT).contains(&channels_count));
+                prop_assert!(model.navigation.current().is_none_or(|index| index < channels_count));
+                prop_assert!(model.navigation.previous().is_none_or(|index| index < channels_count));
+            }
+        }
+
+        #[test]
+        fn change_channel_matches_modular_arithmetic(
+            channels_count in 1usize..=32,
+            start in 1usize..=32,
+            change in -100i32..100,
+        ) {
+            let mut model = model_with_channels(channels_count);
+            let start = (start - 1) % channels_count;
+            update(&mut model, ChannelMessage::SelectChannel(start + 1));
+            update(&mut model, ChannelMessage::ChangeChannel(change));
+
+            let expected = (start as i64 + change as i64).rem_euclid(channels_count as i64) as usize;
+            prop_assert_eq!(model.navigation.current(), Some(expected));
+        }
+
+        #[test]
+        fn full_turn_returns_to_the_same_channel(
+            channels_count in 1usize..=32,
+            start in 1usize..=32,
+        ) {
+            let mut model = model_with_channels(channels_count);
+            let start = (start - 1) % channels_count + 1;
+            update(&mut model, ChannelMessage::SelectChannel(start));
+            update(&mut model, ChannelMessage::ChangeChannel(channels_count as i32));
+            prop_assert_eq!(model.navigation.current(), Some(start - 1));
+        }
+    }
+}
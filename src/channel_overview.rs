@@ -0,0 +1,103 @@
+// Sorting and filtering of the all-channels overview table.
+
+use serde::{Deserialize, Serialize};
+
+use crate::channel_model::ChannelInfo;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OverviewColumn {
+    Channel,
+    Value,
+    Suspicious,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortDirection {
+    Ascending,
+    Descending,
+}
+
+impl SortDirection {
+    pub fn as_symbol(&self) -> &'static str {
+        match self {
+            SortDirection::Ascending => "▲",
+            SortDirection::Descending => "▼",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChannelOverview {
+    pub sort_column: OverviewColumn,
+    pub sort_direction: SortDirection,
+    pub suspicious_only: bool,
+    /// Raw contents of the value range inputs, empty or unparsable means no bound.
+    pub min_value_text: String,
+    pub max_value_text: String,
+}
+
+impl Default for ChannelOverview {
+    fn default() -> Self {
+        ChannelOverview {
+            sort_column: OverviewColumn::Channel,
+            sort_direction: SortDirection::Ascending,
+            suspicious_only: false,
+            min_value_text: String::new(),
+            max_value_text: String::new(),
+        }
+    }
+}
+
+impl ChannelOverview {
+    /// Sorts by `column`, or flips the direction if the table is already sorted by it.
+    pub fn sort_by(&mut self, column: OverviewColumn) {
+        if self.sort_column == column {
+            self.sort_direction = match self.sort_direction {
+                SortDirection::Ascending => SortDirection::Descending,
+                SortDirection::Descending => SortDirection::Ascending,
+            };
+        } else {
+            self.sort_column = column;
+            self.sort_direction = SortDirection::Ascending;
+        }
+    }
+
+    /// Indices of the channels to show, filtered and in display order.
+    pub fn visible_channels(&self, channel_data: &[ChannelInfo]) -> Vec<usize> {
+        let min_value = self.min_value_text.trim().parse::<f64>().ok();
+        let max_value = self.max_value_text.trim().parse::<f64>().ok();
+
+        let mut indices: Vec<usize> = channel_data
+            .iter()
+            .enumerate()
+            .filter(|(_, channel_info)| !self.suspicious_only || channel_info.is_suspicious())
+            .filter(|(_, channel_info)| {
+                min_value.is_none_or(|min| channel_info.value.scalar() >= min)
+            })
+            .filter(|(_, channel_info)| {
+                max_value.is_none_or(|max| channel_info.value.scalar() <= max)
+            })
+            .map(|(index, _)| index)
+            .collect();
+
+        // Channels with equal values keep their channel order
+        indices.sort_by(|&a, &b| match self.sort_column {
+            OverviewColumn::Channel => channel_data[a]
+                .metadata
+                .sort_key(a)
+                .cmp(&channel_data[b].metadata.sort_key(b)),
+            OverviewColumn::Value => channel_data[a]
+                .value
+                .scalar()
+                .total_cmp(&channel_data[b].value.scalar())
+                .then(a.cmp(&b)),
+            OverviewColumn::Suspicious => {
+                (channel_data[a].is_suspicious(), a).cmp(&(channel_data[b].is_suspicious(), b))
+            }
+        });
+        if self.sort_direction == SortDirection::Descending {
+            indices.reverse();
+        }
+        indices
+    }
+}
@@ -0,0 +1,85 @@
+// Aggregate statistics over the current values of all channels.
+
+use crate::channel_model::ChannelInfo;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelStatistics {
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    /// Population standard deviation.
+    pub std_dev: f64,
+    pub median: f64,
+    pub suspicious_count: usize,
+    pub channels_count: usize,
+}
+
+impl ChannelStatistics {
+    /// Statistics of the current values, `None` when there are no channels.
+    pub fn compute(channel_data: &[ChannelInfo]) -> Option<Self> {
+        let mut values: Vec<f64> = channel_data
+            .iter()
+            .map(|channel_info| channel_info.value.scalar())
+            .collect();
+        values.sort_unstable_by(f64::total_cmp);
+
+        let count = values.len();
+        let (&min, &max) = (values.first()?, values.last()?);
+        let mean = values.iter().sum::<f64>() / count as f64;
+        let variance = values
+            .iter()
+            .map(|&value| (value - mean).powi(2))
+            .sum::<f64>()
+            / count as f64;
+        let median = if count.is_multiple_of(2) {
+            (values[count / 2 - 1] + values[count / 2]) / 2.0
+        } else {
+            values[count / 2]
+        };
+
+        Some(ChannelStatistics {
+            min,
+            max,
+            mean,
+            std_dev: variance.sqrt(),
+            median,
+            suspicious_count: channel_data
+                .iter()
+                .filter(|channel_info| channel_info.is_suspicious())
+                .count(),
+            channels_count: count,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channel_value::ChannelValue;
+
+    fn channels(values: &[u32]) -> Vec<ChannelInfo> {
+        values
+            .iter()
+            .map(|&value| ChannelInfo::new(ChannelValue::Unsigned(value), 1))
+            .collect()
+    }
+
+    #[test]
+    fn no_channels_no_statistics() {
+        assert_eq!(ChannelStatistics::compute(&[]), None);
+    }
+
+    #[test]
+    fn odd_and_even_counts() {
+        let statistics = ChannelStatistics::compute(&channels(&[9, 1, 5])).unwrap();
+        assert_eq!((statistics.min, statistics.max), (1.0, 9.0));
+        assert_eq!(statistics.mean, 5.0);
+        assert_eq!(statistics.median, 5.0);
+
+        let statistics = ChannelStatistics::compute(&channels(&[2, 4, 4, 4, 5, 5, 7, 9])).unwrap();
+        assert_eq!(statistics.mean, 5.0);
+        assert_eq!(statistics.std_dev, 2.0);
+        assert_eq!(statistics.median, 4.5);
+        assert_eq!(statistics.suspicious_count, 0);
+    }
+}
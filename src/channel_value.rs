@@ -0,0 +1,338 @@
+// Values reported by channels and the kind of values a session works with.
+// Sensors report unsigned counts, signed temperatures, fractional voltages or several fields
+// at once. Limits, rules, history and statistics look at the `scalar` of a value, which is
+// the first field of multi-field values.
+
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use serde::{Deserialize, Serialize};
+
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const VALUE_TYPE_FLAG: &str = "--value-type";
+pub const VALUE_TYPE_ENV: &str = "CHANNEL_VALUE_TYPE";
+/// Decimals of floating point values, generated values are rounded to them and the slider
+/// moves in steps of the last one (0.1).
+pub const FLOAT_DECIMALS: usize = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ChannelValue {
+    Unsigned(u32),
+    Signed(i64),
+    Float(f64),
+    /// One value per field of `ValueKind::Fields`, in the same order.
+    Fields(Vec<f64>),
+}
+
+impl ChannelValue {
+    /// What limits and rules compare, the first field of multi-field values.
+    pub fn scalar(&self) -> f64 {
+        match self {
+            ChannelValue::Unsigned(value) => *value as f64,
+            ChannelValue::Signed(value) => *value as f64,
+            ChannelValue::Float(value) => *value,
+            ChannelValue::Fields(fields) => fields.first().copied().unwrap_or_default(),
+        }
+    }
+}
+
+/// Plain form read back by `ValueKind::parse_value`, fields are separated by `;`.
+impl fmt::Display for ChannelValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelValue::Unsigned(value) => write!(f, "{value}"),
+            ChannelValue::Signed(value) => write!(f, "{value}"),
+            ChannelValue::Float(value) => write!(f, "{value}"),
+            ChannelValue::Fields(fields) => {
+                let fields: Vec<_> = fields.iter().map(|field| field.to_string()).collect();
+                write!(f, "{}", fields.join(";"))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValueInputError {
+    NotANumber,
+    WrongFieldCount(usize),
+    OutOfRange(RangeInclusive<f64>),
+}
+
+impl fmt::Display for ValueInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueInputError::NotANumber => write!(f, "not a valid number"),
+            ValueInputError::WrongFieldCount(count) => {
+                write!(f, "expected {count} fields separated by ';'")
+            }
+            ValueInputError::OutOfRange(range) => {
+                write!(f, "must be between {} and {}", range.start(), range.end())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ValueKind {
+    /// Whole numbers from 1 to 100, the original values of the app.
+    #[default]
+    Unsigned,
+    Signed,
+    Float,
+    /// Several floating point fields per channel, named in the order they are reported.
+    Fields(Vec<String>),
+}
+
+impl ValueKind {
+    /// Parses `unsigned`, `signed`, `float` or `fields:<name>,<name>,...`.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let (kind, names) = spec.trim().split_once(':').unwrap_or((spec.trim(), ""));
+        match kind {
+            "unsigned" => Some(ValueKind::Unsigned),
+            "signed" => Some(ValueKind::Signed),
+            "float" => Some(ValueKind::Float),
+            "fields" => {
+                let names: Vec<_> = names
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::to_string)
+                    .collect();
+                (!names.is_empty()).then_some(ValueKind::Fields(names))
+            }
+            _ => None,
+        }
+    }
+
+    /// Range of generated values and of the limit slider.
+    pub fn range(&self) -> RangeInclusive<f64> {
+        match self {
+            ValueKind::Unsigned => 1.0..=100.0,
+            ValueKind::Signed => -50.0..=50.0,
+            ValueKind::Float | ValueKind::Fields(_) => 0.0..=10.0,
+        }
+    }
+
+    pub fn step(&self) -> f64 {
+        match self {
+            ValueKind::Unsigned | ValueKind::Signed => 1.0,
+            ValueKind::Float | ValueKind::Fields(_) => 10f64.powi(-(FLOAT_DECIMALS as i32)),
+        }
+    }
+
+    /// Value of channels before their first sample, the lowest value of the range.
+    pub fn default_value(&self) -> ChannelValue {
+        let start = *self.range().start();
+        match self {
+            ValueKind::Unsigned => ChannelValue::Unsigned(start as u32),
+            ValueKind::Signed => ChannelValue::Signed(start as i64),
+            ValueKind::Float => ChannelValue::Float(start),
+            ValueKind::Fields(names) => ChannelValue::Fields(vec![start; names.len()]),
+        }
+    }
+
+    /// Three quarters up the range, 75 for unsigned values as before.
+    pub fn default_limit(&self) -> f64 {
+        let range = self.range();
+        self.round_to_step(range.start() + (range.end() - range.start()) * 0.75)
+    }
+
+    /// Slider values are rounded so floating point steps don't show up as 7.300000000000001.
+    pub fn round_to_step(&self, value: f64) -> f64 {
+        let steps_per_unit = (1.0 / self.step()).round();
+        (value * steps_per_unit).round() / steps_per_unit
+    }
+
+    pub fn decimals(&self) -> usize {
+        match self {
+            ValueKind::Unsigned | ValueKind::Signed => 0,
+            ValueKind::Float | ValueKind::Fields(_) => FLOAT_DECIMALS,
+        }
+    }
+
+    /// Limits, deltas and statistics in the precision of this kind of values.
+    pub fn format_scalar(&self, value: f64) -> String {
+        format!("{value:.*}", self.decimals())
+    }
+
+    /// "x=1.5 y=2.0" for multi-field values, the plain value otherwise, floats with `decimals`.
+    pub fn format_value(&self, value: &ChannelValue) -> String {
+        match (self, value) {
+            (ValueKind::Fields(names), ChannelValue::Fields(fields)) => names
+                .iter()
+                .zip(fields)
+                .map(|(name, field)| format!("{name}={}", self.format_scalar(*field)))
+                .collect::<Vec<_>>()
+                .join(" "),
+            (_, ChannelValue::Float(value)) => self.format_scalar(*value),
+            _ => value.to_string(),
+        }
+    }
+
+    /// Reads the plain form written by `ChannelValue`'s `Display`.
+    pub fn parse_value(&self, text: &str) -> Result<ChannelValue, ValueInputError> {
+        let text = text.trim();
+        // Whole numbers are parsed wider than their type so "-5" for an unsigned value
+        // is reported as out of range, not as garbage
+        let whole = || text.parse::<i64>().map_err(|_| ValueInputError::NotANumber);
+        let float = |text: &str| {
+            text.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|value| value.is_finite())
+                .ok_or(ValueInputError::NotANumber)
+        };
+        match self {
+            ValueKind::Unsigned => u32::try_from(whole()?)
+                .map(ChannelValue::Unsigned)
+                .map_err(|_| ValueInputError::OutOfRange(self.range())),
+            ValueKind::Signed => whole().map(ChannelValue::Signed),
+            ValueKind::Float => float(text).map(ChannelValue::Float),
+            ValueKind::Fields(names) => {
+                let fields = text.split(';').map(float).collect::<Result<Vec<_>, _>>()?;
+                if fields.len() == names.len() {
+                    Ok(ChannelValue::Fields(fields))
+                } else {
+                    Err(ValueInputError::WrongFieldCount(names.len()))
+                }
+            }
+        }
+    }
+
+    /// Checks a value typed in or imported by the user against `range`, every field of
+    /// multi-field values has to be in it.
+    pub fn parse_in_range(
+        &self,
+        text: &str,
+        range: RangeInclusive<f64>,
+    ) -> Result<ChannelValue, ValueInputError> {
+        let value = self.parse_value(text)?;
+        let is_in_range = match &value {
+            ChannelValue::Fields(fields) => fields.iter().all(|field| range.contains(field)),
+            _ => range.contains(&value.scalar()),
+        };
+        if is_in_range {
+            Ok(value)
+        } else {
+            Err(ValueInputError::OutOfRange(range))
+        }
+    }
+}
+
+impl fmt::Display for ValueKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueKind::Unsigned => write!(f, "unsigned"),
+            ValueKind::Signed => write!(f, "signed"),
+            ValueKind::Float => write!(f, "float"),
+            ValueKind::Fields(names) => write!(f, "fields:{}", names.join(",")),
+        }
+    }
+}
+
+/// Kind of values from `--value-type` or `CHANNEL_VALUE_TYPE`, unsigned when not set.
+pub fn kind_from_startup_args() -> ValueKind {
+    let Some(spec) = startup_option(VALUE_TYPE_FLAG, VALUE_TYPE_ENV) else {
+        return ValueKind::default();
+    };
+    ValueKind::parse(&spec).unwrap_or_else(|| {
+        eprintln!("Unknown value type '{spec}', using unsigned values.");
+        ValueKind::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_read_back_their_plain_form() {
+        let kinds_and_values = [
+            (ValueKind::Unsigned, ChannelValue::Unsigned(42)),
+            (ValueKind::Signed, ChannelValue::Signed(-7)),
+            (ValueKind::Float, ChannelValue::Float(3.25)),
+            (
+                ValueKind::Fields(vec!["x".to_string(), "y".to_string()]),
+                ChannelValue::Fields(vec![1.5, -2.0]),
+            ),
+        ];
+        for (kind, value) in kinds_and_values {
+            assert_eq!(kind.parse_value(&value.to_string()), Ok(value));
+        }
+    }
+
+    #[test]
+    fn values_are_checked_against_the_range() {
+        let kind = ValueKind::Unsigned;
+        assert_eq!(
+            kind.parse_in_range(" 42 ", kind.range()),
+            Ok(ChannelValue::Unsigned(42))
+        );
+        assert_eq!(
+            kind.parse_in_range("abc", kind.range()),
+            Err(ValueInputError::NotANumber)
+        );
+        assert_eq!(
+            kind.parse_in_range("0", kind.range()),
+            Err(ValueInputError::OutOfRange(kind.range()))
+        );
+        assert_eq!(
+            kind.parse_in_range("-5", kind.range()),
+            Err(ValueInputError::OutOfRange(kind.range()))
+        );
+
+        let kind = ValueKind::Fields(vec!["x".to_string(), "y".to_string()]);
+        assert_eq!(
+            kind.parse_in_range("1", kind.range()),
+            Err(ValueInputError::WrongFieldCount(2))
+        );
+        assert_eq!(
+            kind.parse_in_range("11;1", kind.range()),
+            Err(ValueInputError::OutOfRange(kind.range()))
+        );
+        assert_eq!(
+            kind.parse_in_range("1;-0.5", kind.range()),
+            Err(ValueInputError::OutOfRange(kind.range()))
+        );
+        assert_eq!(
+            kind.parse_in_range("0;10", kind.range()),
+            Ok(ChannelValue::Fields(vec![0.0, 10.0]))
+        );
+    }
+
+    #[test]
+    fn float_precision_matches_the_step() {
+        let kind = ValueKind::Float;
+        assert_eq!(kind.step(), 0.1);
+        assert_eq!(kind.round_to_step(7.34), 7.3);
+        assert_eq!(kind.format_scalar(7.3), "7.3");
+        assert_eq!(kind.format_value(&ChannelValue::Float(3.0)), "3.0");
+        assert_eq!(
+            ValueKind::Fields(vec!["x".to_string(), "y".to_string()])
+                .format_value(&ChannelValue::Fields(vec![1.5, 2.0])),
+            "x=1.5 y=2.0"
+        );
+    }
+
+    #[test]
+    fn channels_start_at_the_lowest_value_of_their_kind() {
+        assert_eq!(
+            ValueKind::Unsigned.default_value(),
+            ChannelValue::Unsigned(1)
+        );
+        assert_eq!(ValueKind::Signed.default_value(), ChannelValue::Signed(-50));
+        assert_eq!(
+            ValueKind::Fields(vec!["x".to_string(), "y".to_string()]).default_value(),
+            ChannelValue::Fields(vec![0.0, 0.0])
+        );
+    }
+
+    #[test]
+    fn default_limits() {
+        assert_eq!(ValueKind::Unsigned.default_limit(), 75.0);
+        assert_eq!(ValueKind::Signed.default_limit(), 25.0);
+        assert_eq!(ValueKind::Float.default_limit(), 7.5);
+    }
+}
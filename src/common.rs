@@ -1,7 +1,42 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+//*  Constants */
+/// Directory of the app in the user's config directory, shared by everything the app saves.
+pub const APP_CONFIG_DIR_NAME: &str = "gui_test_project";
+
+/// Channel numbered from 1 as in the UI.
+pub type ChannelNumber = usize;
+
 #[derive(Default, PartialEq, Eq, Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ApplicationTab {
     #[default]
     Home,
     Settings,
     About,
 }
+
+impl ApplicationTab {
+    /// Tab to the right of this one, wrapping around at the end.
+    pub fn next(&self) -> Self {
+        match self {
+            ApplicationTab::Home => ApplicationTab::Settings,
+            ApplicationTab::Settings => ApplicationTab::About,
+            ApplicationTab::About => ApplicationTab::Home,
+        }
+    }
+}
+
+/// UTC time of day as `HH:MM:SS`.
+pub fn format_time_of_day(timestamp: SystemTime) -> String {
+    let seconds = timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / 3600 % 24,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
@@ -0,0 +1,216 @@
+// Table comparing channels side by side, one row per channel and one column per definition.
+// Rows are compared against the first one, so the columns decide what a comparison shows
+// and the table itself only knows about layout.
+
+use iced::{
+    widget::{button, scrollable, text, text_input, Column, Row},
+    Color, Element, Length, Theme,
+};
+
+/// Content of one table cell.
+#[derive(Debug, Clone, Default)]
+pub struct Cell {
+    pub text: String,
+    pub color: Option<Color>,
+    /// Shown below editable cells whose text can't be applied.
+    pub error: Option<String>,
+}
+
+impl Cell {
+    pub fn new(text: impl Into<String>) -> Self {
+        Cell {
+            text: text.into(),
+            color: None,
+            error: None,
+        }
+    }
+
+    pub fn with_color(mut self, color: Option<Color>) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_error(mut self, error: Option<String>) -> Self {
+        self.error = error;
+        self
+    }
+}
+
+/// Messages of an editable column, given the channel of the edited row.
+pub struct CellEditor<Message> {
+    pub on_input: fn(usize, String) -> Message,
+    pub on_submit: fn(usize) -> Message,
+}
+
+pub struct TableColumn<'a, Message> {
+    pub header: &'static str,
+    /// Share of the table width, as with `Length::FillPortion`.
+    pub width: u16,
+    /// Shown in a read-only text input so the value can be selected and copied.
+    pub is_copyable: bool,
+    /// Cell of a row showing a channel, given the channel of the first row to compare with
+    /// (`None` in the first row itself). Rows without a channel get an empty cell.
+    pub cell: Box<dyn Fn(usize, Option<usize>) -> Cell + 'a>,
+    pub editor: Option<CellEditor<Message>>,
+}
+
+impl<'a, Message> TableColumn<'a, Message> {
+    pub fn new(
+        header: &'static str,
+        width: u16,
+        cell: impl Fn(usize, Option<usize>) -> Cell + 'a,
+    ) -> Self {
+        TableColumn {
+            header,
+            width,
+            is_copyable: false,
+            cell: Box::new(cell),
+            editor: None,
+        }
+    }
+
+    pub fn copyable(mut self) -> Self {
+        self.is_copyable = true;
+        self
+    }
+
+    pub fn editable(
+        mut self,
+        on_input: fn(usize, String) -> Message,
+        on_submit: fn(usize) -> Message,
+    ) -> Self {
+        self.editor = Some(CellEditor {
+            on_input,
+            on_submit,
+        });
+        self
+    }
+}
+
+pub struct TableRow<Message> {
+    pub label: String,
+    pub channel_index: Option<usize>,
+    /// Buttons at the end of the row.
+    pub actions: Vec<(&'static str, Message)>,
+}
+
+// Copyable cell showing its value in the cell colour, otherwise styled as usual
+struct ColoredTextInput {
+    value_color: Color,
+}
+
+impl text_input::StyleSheet for ColoredTextInput {
+    type Style = Theme;
+
+    fn active(&self, style: &Theme) -> text_input::Appearance {
+        style.active(&iced::theme::TextInput::Default)
+    }
+    fn focused(&self, style: &Theme) -> text_input::Appearance {
+        style.focused(&iced::theme::TextInput::Default)
+    }
+    fn placeholder_color(&self, style: &Theme) -> Color {
+        style.placeholder_color(&iced::theme::TextInput::Default)
+    }
+    fn value_color(&self, _style: &Theme) -> Color {
+        self.value_color
+    }
+    fn disabled_color(&self, style: &Theme) -> Color {
+        style.disabled_color(&iced::theme::TextInput::Default)
+    }
+    fn selection_color(&self, style: &Theme) -> Color {
+        style.selection_color(&iced::theme::TextInput::Default)
+    }
+    fn disabled(&self, style: &Theme) -> text_input::Appearance {
+        style.disabled(&iced::theme::TextInput::Default)
+    }
+}
+
+/// Builds the table. Copyable cells send `ignored_input` when typed into, which keeps them
+/// enabled (and copyable) while their value never changes. Cell errors use `error_color`.
+pub fn view<'a, Message: Clone + 'a>(
+    rows: Vec<TableRow<Message>>,
+    columns: &[TableColumn<'_, Message>],
+    ignored_input: Message,
+    error_color: Color,
+) -> Element<'a, Message> {
+    let baseline = rows.first().and_then(|row| row.channel_index);
+
+    let mut header = Row::new()
+        .spacing(5)
+        .push(text("").width(Length::FillPortion(1)));
+    for column in columns {
+        header = header.push(
+            text(column.header)
+                .width(Length::FillPortion(column.width))
+                .horizontal_alignment(iced::alignment::Horizontal::Center),
+        );
+    }
+    header = header.push(text("Actions").width(Length::FillPortion(2)));
+
+    let mut table_rows = Column::new().spacing(10);
+    for (position, row) in rows.into_iter().enumerate() {
+        let baseline = baseline.filter(|_| position > 0);
+        let mut table_row = Row::new()
+            .spacing(5)
+            .align_items(iced::Alignment::Center)
+            .push(
+                text(format!("{}:", row.label))
+                    .width(Length::FillPortion(1))
+                    .horizontal_alignment(iced::alignment::Horizontal::Right),
+            );
+
+        for column in columns {
+            let cell = row
+                .channel_index
+                .map(|index| (column.cell)(index, baseline))
+                .unwrap_or_default();
+            let width = Length::FillPortion(column.width);
+            table_row = if column.is_copyable || column.editor.is_some() {
+                let mut input = text_input(column.header, &cell.text);
+                input = match (&column.editor, row.channel_index) {
+                    (Some(editor), Some(index)) => {
+                        let on_input = editor.on_input;
+                        input
+                            .on_input(move |text| on_input(index, text))
+                            .on_submit((editor.on_submit)(index))
+                    }
+                    _ => {
+                        let ignored_input = ignored_input.clone();
+                        input.on_input(move |_| ignored_input.clone())
+                    }
+                };
+                if let Some(color) = cell.color {
+                    input =
+                        input.style(iced::theme::TextInput::Custom(Box::new(ColoredTextInput {
+                            value_color: color,
+                        })));
+                }
+                let mut content = Column::new().width(width).push(input);
+                if let Some(error) = cell.error {
+                    content = content.push(text(error).size(14).style(error_color));
+                }
+                table_row.push(content)
+            } else {
+                let mut content = text(cell.text)
+                    .width(width)
+                    .horizontal_alignment(iced::alignment::Horizontal::Center);
+                if let Some(color) = cell.color {
+                    content = content.style(color);
+                }
+                table_row.push(content)
+            };
+        }
+
+        let mut actions = Row::new().spacing(5);
+        for (label, message) in row.actions {
+            actions = actions.push(button(text(label)).on_press(message));
+        }
+        table_rows = table_rows.push(table_row.push(actions.width(Length::FillPortion(2))));
+    }
+
+    Column::new()
+        .spacing(10)
+        .push(header)
+        .push(scrollable(table_rows).height(Length::Fill))
+        .into()
+}
@@ -0,0 +1,159 @@
+// Log of suspicious/normal transitions of the channels.
+
+use std::collections::VecDeque;
+use std::time::SystemTime;
+
+use crate::channel_value::ChannelValue;
+use crate::common::format_time_of_day;
+
+//*  Constants */
+pub const MAX_EVENTS: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Transition {
+    /// Channel became suspicious, with the explanation of the rule that fired.
+    BecameSuspicious(String),
+    BecameNormal,
+}
+
+#[derive(Debug, Clone)]
+pub struct AlarmEvent {
+    pub id: u64,
+    pub timestamp: SystemTime,
+    pub channel_index: usize,
+    pub value: ChannelValue,
+    /// Limit of the rule that fired, or of the one that stopped firing.
+    pub limit: f64,
+    pub transition: Transition,
+    pub acknowledged: bool,
+}
+
+impl AlarmEvent {
+    pub fn describe(&self) -> String {
+        let state = match &self.transition {
+            Transition::BecameSuspicious(reason) => format!("suspicious ({reason})"),
+            Transition::BecameNormal => "normal".to_string(),
+        };
+        format!(
+            "[{}] Channel {}: {} (limit {}) -> {}",
+            format_time_of_day(self.timestamp),
+            self.channel_index + 1,
+            self.value,
+            self.limit,
+            state
+        )
+    }
+}
+
+/// Keeps the latest `MAX_EVENTS` events, oldest first.
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: VecDeque<AlarmEvent>,
+    next_id: u64,
+}
+
+impl EventLog {
+    pub fn record(
+        &mut self,
+        channel_index: usize,
+        value: ChannelValue,
+        limit: f64,
+        transition: Transition,
+    ) {
+        if self.events.len() == MAX_EVENTS {
+            self.events.pop_front();
+        }
+        self.events.push_back(AlarmEvent {
+            id: self.next_id,
+            timestamp: SystemTime::now(),
+            channel_index,
+            value,
+            limit,
+            transition,
+            acknowledged: false,
+        });
+        self.next_id += 1;
+    }
+
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &AlarmEvent> {
+        self.events.iter()
+    }
+
+    pub fn unacknowledged_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|event| !event.acknowledged)
+            .count()
+    }
+
+    pub fn acknowledge(&mut self, id: u64) {
+        if let Some(event) = self.events.iter_mut().find(|event| event.id == id) {
+            event.acknowledged = true;
+        }
+    }
+
+    pub fn acknowledge_all(&mut self) {
+        for event in &mut self.events {
+            event.acknowledged = true;
+        }
+    }
+
+    pub fn clear_acknowledged(&mut self) {
+        self.events.retain(|event| !event.acknowledged);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log_with_events(count: usize) -> EventLog {
+        let mut event_log = EventLog::default();
+        for index in 0..count {
+            event_log.record(
+                index,
+                ChannelValue::Unsigned(80),
+                75.0,
+                Transition::BecameSuspicious("> 75".to_string()),
+            );
+        }
+        event_log
+    }
+
+    fn ids(event_log: &EventLog) -> Vec<u64> {
+        event_log.events().map(|event| event.id).collect()
+    }
+
+    #[test]
+    fn acknowledging_events() {
+        let mut event_log = log_with_events(3);
+        assert_eq!(event_log.unacknowledged_count(), 3);
+
+        event_log.acknowledge(1);
+        event_log.acknowledge(7);
+        assert_eq!(event_log.unacknowledged_count(), 2);
+
+        event_log.clear_acknowledged();
+        assert_eq!(ids(&event_log), [0, 2]);
+
+        event_log.acknowledge_all();
+        assert_eq!(event_log.unacknowledged_count(), 0);
+        event_log.clear_acknowledged();
+        assert!(ids(&event_log).is_empty());
+    }
+
+    #[test]
+    fn oldest_events_make_room_for_new_ones() {
+        let event_log = log_with_events(MAX_EVENTS + 2);
+        let ids = ids(&event_log);
+        assert_eq!(ids.len(), MAX_EVENTS);
+        assert_eq!(ids.first(), Some(&2));
+    }
+
+    #[test]
+    fn events_describe_the_limit_and_reason() {
+        let event_log = log_with_events(1);
+        let description = event_log.events().next().unwrap().describe();
+        assert!(description.ends_with("Channel 1: 80 (limit 75) -> suspicious (> 75)"));
+    }
+}
@@ -0,0 +1,350 @@
+// Export of the channel snapshot, sample history and event log to CSV and JSON.
+// Both the UI and the headless `--export` mode go through `Snapshot`, so scripts get
+// exactly what the Export buttons write.
+
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::channel_model::ChannelInfo;
+use crate::channel_value::ChannelValue;
+use crate::event_log::{EventLog, Transition};
+use crate::startup_args::startup_option;
+use crate::suspicious_rules::RuleSet;
+
+//*  Constants */
+pub const EXPORT_FLAG: &str = "--export";
+pub const EXPORT_ENV: &str = "CHANNEL_EXPORT";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// JSON for `.json` files, CSV for anything else.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+/// A value as written to the files, numbers stay numbers and multi-field values are written
+/// in their plain `;`-separated form so CSV keeps one column per value.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum ExportedValue {
+    Unsigned(u32),
+    Signed(i64),
+    Float(f64),
+    Text(String),
+}
+
+impl ExportedValue {
+    /// Whole limits are written as "75" rather than "75.0".
+    pub fn from_scalar(value: f64) -> Self {
+        if value.fract() == 0.0 {
+            ExportedValue::Signed(value as i64)
+        } else {
+            ExportedValue::Float(value)
+        }
+    }
+}
+
+impl From<&ChannelValue> for ExportedValue {
+    fn from(value: &ChannelValue) -> Self {
+        match value {
+            ChannelValue::Unsigned(value) => ExportedValue::Unsigned(*value),
+            ChannelValue::Signed(value) => ExportedValue::Signed(*value),
+            ChannelValue::Float(value) => ExportedValue::Float(*value),
+            ChannelValue::Fields(_) => ExportedValue::Text(value.to_string()),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ChannelRow {
+    pub channel: usize,
+    pub value: ExportedValue,
+    pub suspicious: bool,
+    /// Limit of the rule that flagged the channel, or of its first rule while it looks normal.
+    pub limit: ExportedValue,
+    pub rule: Option<String>,
+    /// Value set by hand instead of coming from the data source.
+    pub overridden: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HistoryRow {
+    pub channel: usize,
+    pub timestamp_ms: u64,
+    pub value: ExportedValue,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EventRow {
+    pub id: u64,
+    pub timestamp_ms: u64,
+    pub channel: usize,
+    pub value: ExportedValue,
+    pub limit: ExportedValue,
+    pub transition: String,
+    pub acknowledged: bool,
+}
+
+/// Everything that gets exported, channels are numbered from 1 as in the UI.
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub exported_at_ms: u64,
+    pub channels: Vec<ChannelRow>,
+    pub history: Vec<HistoryRow>,
+    pub events: Vec<EventRow>,
+}
+
+impl Snapshot {
+    pub fn new(
+        channel_data: &[ChannelInfo],
+        rules: &RuleSet,
+        current_limit: f64,
+        event_log: &EventLog,
+    ) -> Self {
+        let channels = channel_data
+            .iter()
+            .enumerate()
+            .map(|(index, channel_info)| ChannelRow {
+                channel: index + 1,
+                value: ExportedValue::from(&channel_info.value),
+                suspicious: channel_info.is_suspicious(),
+                limit: ExportedValue::from_scalar(
+                    channel_info
+                        .suspicious_rule
+                        .as_ref()
+                        .or(rules.rules_for(index).first())
+                        .map_or(current_limit, |rule| {
+                            rule.limit(channel_info.value.scalar(), current_limit)
+                        }),
+                ),
+                rule: channel_info
+                    .suspicious_rule
+                    .as_ref()
+                    .map(|rule| rule.explain(current_limit)),
+                overridden: channel_info.is_overridden,
+            })
+            .collect();
+
+        let history = channel_data
+            .iter()
+            .enumerate()
+            .flat_map(|(index, channel_info)| {
+                channel_info
+                    .history
+                    .samples()
+                    .map(move |sample| HistoryRow {
+                        channel: index + 1,
+                        timestamp_ms: unix_millis(sample.timestamp),
+                        value: ExportedValue::from(&sample.value),
+                    })
+            })
+            .collect();
+
+        let events = event_log
+            .events()
+            .map(|event| EventRow {
+                id: event.id,
+                timestamp_ms: unix_millis(event.timestamp),
+                channel: event.channel_index + 1,
+                value: ExportedValue::from(&event.value),
+                limit: ExportedValue::from_scalar(event.limit),
+                transition: match &event.transition {
+                    Transition::BecameSuspicious(reason) => format!("suspicious ({reason})"),
+                    Transition::BecameNormal => "normal".to_string(),
+                },
+                acknowledged: event.acknowledged,
+            })
+            .collect();
+
+        Snapshot {
+            exported_at_ms: unix_millis(SystemTime::now()),
+            channels,
+            history,
+            events,
+        }
+    }
+
+    /// Writes the snapshot and returns the paths of the written files.
+    ///
+    /// JSON goes into a single file. CSV writes the channels to `path` and, when there is
+    /// anything to write, the history and events next to it with `_history`/`_events` suffixes.
+    pub fn write(&self, path: &Path, format: ExportFormat) -> io::Result<Vec<PathBuf>> {
+        match format {
+            ExportFormat::Json => {
+                let writer = BufWriter::new(File::create(path)?);
+                serde_json::to_writer_pretty(writer, self)?;
+                Ok(vec![path.to_path_buf()])
+            }
+            ExportFormat::Csv => {
+                let mut written = vec![path.to_path_buf()];
+                write_csv(path, &self.channels)?;
+
+                if !self.history.is_empty() {
+                    let history_path = suffixed_path(path, "history");
+                    write_csv(&history_path, &self.history)?;
+                    written.push(history_path);
+                }
+                if !self.events.is_empty() {
+                    let events_path = suffixed_path(path, "events");
+                    write_csv(&events_path, &self.events)?;
+                    written.push(events_path);
+                }
+                Ok(written)
+            }
+        }
+    }
+}
+
+/// Path given with `--export` or `CHANNEL_EXPORT`, the app exports one snapshot there and exits.
+pub fn path_from_startup_args() -> Option<PathBuf> {
+    startup_option(EXPORT_FLAG, EXPORT_ENV).map(PathBuf::from)
+}
+
+fn write_csv<T: Serialize>(path: &Path, rows: &[T]) -> io::Result<()> {
+    let mut writer = csv::Writer::from_path(path)?;
+    for row in rows {
+        writer.serialize(row)?;
+    }
+    writer.flush()
+}
+
+// "snapshot.csv" -> "snapshot_history.csv"
+fn suffixed_path(path: &Path, suffix: &str) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+    path.with_file_name(format!("{stem}_{suffix}{extension}"))
+}
+
+fn unix_millis(timestamp: SystemTime) -> u64 {
+    timestamp
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use serde_json::json;
+
+    use crate::channel_model::{self, ChannelMessage, ChannelModel};
+    use crate::channel_value::ValueKind;
+    use crate::import;
+    use crate::test_util::TempDir;
+
+    // Channel 2 is above the slider limit, channel 3 outside its own band
+    fn snapshot() -> Snapshot {
+        let rules = RuleSet::parse("limit; 3: out-of-band 10 90").unwrap();
+        let mut model = ChannelModel::new(3, 8, &ValueKind::Unsigned, 75.0, rules);
+        channel_model::update(
+            &mut model,
+            ChannelMessage::SampleReceived([10, 80, 95].map(ChannelValue::Unsigned).to_vec()),
+        );
+        Snapshot::new(
+            &model.channel_data,
+            &model.rules,
+            model.current_suspicious_limit,
+            &model.event_log,
+        )
+    }
+
+    fn csv_column(path: &Path, column: &str) -> Vec<String> {
+        csv::Reader::from_path(path)
+            .unwrap()
+            .deserialize::<HashMap<String, String>>()
+            .map(|row| row.unwrap()[column].clone())
+            .collect()
+    }
+
+    #[test]
+    fn csv_exports_import_back() {
+        let temp_dir = TempDir::new("export_csv");
+        let path = temp_dir.file("snapshot.csv");
+        let written = snapshot().write(&path, ExportFormat::Csv).unwrap();
+        assert_eq!(
+            written,
+            [
+                path.clone(),
+                temp_dir.file("snapshot_history.csv"),
+                temp_dir.file("snapshot_events.csv"),
+            ]
+        );
+
+        let kind = ValueKind::Unsigned;
+        let report =
+            import::import_channels(&path, ExportFormat::Csv, 3, &kind, kind.range()).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(
+            report.values,
+            [
+                (0, ChannelValue::Unsigned(10)),
+                (1, ChannelValue::Unsigned(80)),
+                (2, ChannelValue::Unsigned(95)),
+            ]
+        );
+        assert_eq!(csv_column(&path, "limit"), ["75", "75", "90"]);
+        assert_eq!(
+            csv_column(&written[2], "transition"),
+            ["suspicious (> 75)", "suspicious (out of 10..90)"]
+        );
+    }
+
+    #[test]
+    fn json_exports_hold_channels_history_and_events() {
+        let temp_dir = TempDir::new("export_json");
+        let path = temp_dir.file("snapshot.json");
+        let written = snapshot().write(&path, ExportFormat::Json).unwrap();
+        assert_eq!(written, [path.as_path()]);
+
+        let document: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(document["exported_at_ms"].is_u64());
+        assert_eq!(
+            document["channels"][2],
+            json!({
+                "channel": 3,
+                "value": 95,
+                "suspicious": true,
+                "limit": 90,
+                "rule": "out of 10..90",
+                "overridden": false,
+            })
+        );
+        assert_eq!(document["channels"][0]["rule"], json!(null));
+        assert_eq!(document["history"].as_array().unwrap().len(), 3);
+        let event = &document["events"][0];
+        assert_eq!(
+            (&event["channel"], &event["limit"], &event["transition"]),
+            (&json!(2), &json!(75), &json!("suspicious (> 75)"))
+        );
+    }
+}
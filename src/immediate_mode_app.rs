@@ -11,16 +11,35 @@
 
 // TODO setting to not update frame if no input from user + side effect
 
-use eframe::egui::{self, Align, CentralPanel, Grid, Layout, TopBottomPanel, Window};
+use std::path::PathBuf;
+use std::time::Instant;
 
-use crate::common::ApplicationTab;
+use eframe::egui::{
+    self, Align, Align2, Area, CentralPanel, Frame, Grid, Layout, ScrollArea, SidePanel,
+    TopBottomPanel, Window,
+};
+
+use crate::app_theme::{self, PaletteColor, Rgb, ThemeChoice};
+use crate::common::{format_time_of_day, ApplicationTab};
+use crate::key_bindings::{KeyBindings, KeyCombo, ShortcutAction};
+use crate::notifications::{Notifications, Severity, TOAST_MARGIN, TOAST_WIDTH};
 
 pub fn run() -> Result<(), eframe::Error> {
     let options = eframe::NativeOptions::default();
     eframe::run_native(
         "Immediate Mode App",
         options,
-        Box::new(|_cc| Ok(Box::new(ImmediateModeApp::default()))),
+        Box::new(|cc| {
+            let theme_choice = app_theme::from_startup_args();
+            apply_theme(&cc.egui_ctx, &theme_choice);
+            Ok(Box::new(ImmediateModeApp {
+                key_bindings: KeyBindings::from_startup_args(KeyBindings::immediate_defaults()),
+                theme_choice,
+                custom_palette: theme_choice.palette(),
+                theme_path: app_theme::theme_file_path(),
+                ..Default::default()
+            }))
+        }),
     )
 }
 
@@ -29,36 +48,179 @@ pub struct UserInfo {
     pub age: u32,
 }
 
-#[derive(Default)]
 pub struct ImmediateModeApp {
     active_tab: ApplicationTab,
     show_main_modal: bool,
-    show_save_settings_modal: bool,
+    key_bindings: KeyBindings,
+    theme_choice: ThemeChoice,
+    custom_palette: app_theme::Palette,
+    theme_path: Option<PathBuf>,
+    /// Set while the theme differs from the saved one, it's saved once the pointer is released.
+    is_theme_unsaved: bool,
+    notifications: Notifications,
+    is_notification_history_visible: bool,
 
     saved_user_info: Option<UserInfo>,
     temp_user_name: String,
     temp_user_age: u32,
 }
 
+impl Default for ImmediateModeApp {
+    fn default() -> Self {
+        ImmediateModeApp {
+            active_tab: ApplicationTab::default(),
+            show_main_modal: false,
+            key_bindings: KeyBindings::immediate_defaults(),
+            theme_choice: ThemeChoice::default(),
+            custom_palette: ThemeChoice::default().palette(),
+            theme_path: None,
+            is_theme_unsaved: false,
+            notifications: Notifications::default(),
+            is_notification_history_visible: false,
+            saved_user_info: None,
+            temp_user_name: String::new(),
+            temp_user_age: 0,
+        }
+    }
+}
+
 impl eframe::App for ImmediateModeApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.handle_shortcuts(ctx);
+
         TopBottomPanel::top("top_panel").show(ctx, |ui| {
             ui.horizontal(|ui| {
                 ui.selectable_value(&mut self.active_tab, ApplicationTab::Home, "Home");
                 ui.selectable_value(&mut self.active_tab, ApplicationTab::Settings, "Settings");
                 ui.selectable_value(&mut self.active_tab, ApplicationTab::About, "About");
+                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                    ui.toggle_value(&mut self.is_notification_history_visible, "Notifications");
+                });
             });
         });
 
+        if self.is_notification_history_visible {
+            SidePanel::right("notification_history").show(ctx, |ui| {
+                self.show_notification_history(ui);
+            });
+        }
+
         CentralPanel::default().show(ctx, |ui| match self.active_tab {
             ApplicationTab::Home => self.show_home_tab(ctx, ui),
             ApplicationTab::Settings => self.show_settings_tab(ctx, ui),
             ApplicationTab::About => self.show_about_tab(ctx, ui),
         });
+
+        self.show_toasts(ctx);
     }
 }
 
 impl ImmediateModeApp {
+    // Consumes the bound keys so egui doesn't also use them, e.g. Tab for focus changes
+    fn handle_shortcuts(&mut self, ctx: &egui::Context) {
+        let mut triggered = Vec::new();
+        ctx.input_mut(|input| {
+            for (combo, action) in self.key_bindings.bindings() {
+                if let Some((modifiers, key)) = egui_shortcut(combo) {
+                    if input.consume_key(modifiers, key) {
+                        triggered.push(action.clone());
+                    }
+                }
+            }
+        });
+
+        for action in triggered {
+            match action {
+                ShortcutAction::NextTab => self.active_tab = self.active_tab.next(),
+                ShortcutAction::CloseModal => self.show_main_modal = false,
+                ShortcutAction::OpenUserInfo => {
+                    self.active_tab = ApplicationTab::Home;
+                    self.show_main_modal = true;
+                }
+                ShortcutAction::SaveSettings => self.save_settings(),
+                _ => {}
+            }
+        }
+    }
+
+    fn save_settings(&mut self) {
+        self.saved_user_info = Some(UserInfo {
+            name: self.temp_user_name.clone(),
+            age: self.temp_user_age,
+        }); // TODO what should be instead of clone?
+        if self.temp_user_name.trim().is_empty() {
+            self.notifications
+                .push(Severity::Warning, "Settings saved without a name");
+        } else {
+            self.notifications
+                .push(Severity::Success, "Settings saved!");
+        }
+    }
+
+    // Toasts stack up in the bottom right corner above everything else, newest at the bottom
+    fn show_toasts(&mut self, ctx: &egui::Context) {
+        self.notifications.expire(Instant::now());
+        let palette = self.theme_choice.palette();
+        let mut dismissed = Vec::new();
+        Area::new(egui::Id::new("toasts"))
+            .anchor(Align2::RIGHT_BOTTOM, [-TOAST_MARGIN, -TOAST_MARGIN])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                for toast in self.notifications.visible() {
+                    let color = egui_color(toast.severity.color(&palette));
+                    Frame::popup(ui.style())
+                        .stroke(egui::Stroke::new(2.0, color))
+                        .show(ui, |ui| {
+                            ui.set_width(TOAST_WIDTH);
+                            ui.horizontal(|ui| {
+                                ui.colored_label(color, toast.severity.to_string());
+                                ui.label(&toast.text);
+                                ui.with_layout(Layout::right_to_left(Align::Center), |ui| {
+                                    if ui.small_button("x").clicked() {
+                                        dismissed.push(toast.id);
+                                    }
+                                });
+                            });
+                        });
+                }
+            });
+        for id in dismissed {
+            self.notifications.dismiss(id);
+        }
+
+        // Nothing else repaints an idle window, so wake up when the next toast times out
+        if let Some(timeout) = self.notifications.next_timeout(Instant::now()) {
+            ctx.request_repaint_after(timeout);
+        }
+    }
+
+    fn show_notification_history(&mut self, ui: &mut egui::Ui) {
+        let palette = self.theme_choice.palette();
+        ui.heading(format!(
+            "Notifications ({})",
+            self.notifications.history_len()
+        ));
+        if ui.button("Clear history").clicked() {
+            self.notifications.clear_history();
+        }
+        ui.separator();
+        ScrollArea::vertical().show(ui, |ui| {
+            Grid::new("notification_history_grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    for toast in self.notifications.history().rev() {
+                        ui.label(format_time_of_day(toast.created));
+                        ui.colored_label(
+                            egui_color(toast.severity.color(&palette)),
+                            toast.severity.to_string(),
+                        );
+                        ui.label(&toast.text);
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
     // Layout and content for the "Home" tab
     fn show_home_tab(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
         ui.heading("Home Tab");
@@ -119,29 +281,77 @@ impl ImmediateModeApp {
 
             ui.horizontal(|ui| {
                 if ui.button("Save Settings").clicked() {
-                    self.show_save_settings_modal = true;
-                    self.saved_user_info = Some(UserInfo {
-                        name: self.temp_user_name.clone(),
-                        age: self.temp_user_age,
-                    }); // TODO what should be instead of clone?
+                    self.save_settings();
                 }
             });
 
-            if self.show_save_settings_modal {
-                Window::new("Info")
-                    .collapsible(false)
-                    .resizable(false)
-                    .show(ctx, |ui| {
-                        ui.label("Settings saved!");
-
-                        if ui.button("Close").clicked() {
-                            self.show_save_settings_modal = false;
-                        }
-                    });
-            }
+            ui.separator();
+            self.show_theme_settings(ctx, ui);
+
+            ui.separator();
+            ui.label("Keyboard shortcuts:");
+            Grid::new("shortcuts_grid").striped(true).show(ui, |ui| {
+                for (combo, action) in self.key_bindings.bindings() {
+                    ui.label(combo.to_string());
+                    ui.label(action.to_string());
+                    ui.end_row();
+                }
+            });
         });
     }
 
+    fn show_theme_settings(&mut self, ctx: &egui::Context, ui: &mut egui::Ui) {
+        let previous_choice = self.theme_choice;
+
+        egui::ComboBox::from_label("Theme")
+            .selected_text(self.theme_choice.to_string())
+            .show_ui(ui, |ui| {
+                for choice in ThemeChoice::all(self.custom_palette) {
+                    ui.selectable_value(&mut self.theme_choice, choice, choice.to_string());
+                }
+            });
+
+        if let ThemeChoice::Custom(_) = self.theme_choice {
+            Grid::new("palette_grid").show(ui, |ui| {
+                for which in PaletteColor::ALL {
+                    let mut rgb = self.custom_palette.color(which);
+                    ui.label(which.name());
+                    if ui.color_edit_button_srgb(&mut rgb).changed() {
+                        self.custom_palette.set_color(which, rgb);
+                    }
+                    ui.end_row();
+                }
+            });
+            self.theme_choice = ThemeChoice::Custom(self.custom_palette);
+        }
+
+        // Colour edits of the custom theme change it on every drag, only a switch is announced
+        if std::mem::discriminant(&self.theme_choice) != std::mem::discriminant(&previous_choice) {
+            self.notifications.push(
+                Severity::Info,
+                format!("Theme changed to {}", self.theme_choice),
+            );
+        }
+        if self.theme_choice != previous_choice {
+            apply_theme(ctx, &self.theme_choice);
+            self.is_theme_unsaved = true;
+        }
+
+        // Colour drags change the theme on every frame, the file is written once they end
+        if self.is_theme_unsaved && !ctx.input(|input| input.pointer.any_down()) {
+            self.is_theme_unsaved = false;
+            if let Some(path) = &self.theme_path {
+                // A failure repeated on every colour change shows one toast, not a stack of copies
+                if let Err(err) = self.theme_choice.save(path) {
+                    self.notifications.push_unique(
+                        Severity::Error,
+                        format!("Can't save theme to {}: {err}", path.display()),
+                    );
+                }
+            }
+        }
+    }
+
     // Layout and content for the "About" tab
     fn show_about_tab(&mut self, _: &egui::Context, ui: &mut egui::Ui) {
         ui.with_layout(Layout::top_down(Align::LEFT), |ui| {
@@ -151,3 +361,38 @@ impl ImmediateModeApp {
         });
     }
 }
+
+fn egui_color([red, green, blue]: Rgb) -> egui::Color32 {
+    egui::Color32::from_rgb(red, green, blue)
+}
+
+fn apply_theme(ctx: &egui::Context, choice: &ThemeChoice) {
+    let palette = choice.palette();
+    let mut visuals = if palette.is_dark() {
+        egui::Visuals::dark()
+    } else {
+        egui::Visuals::light()
+    };
+    visuals.panel_fill = egui_color(palette.background);
+    visuals.window_fill = egui_color(palette.background);
+    visuals.override_text_color = Some(egui_color(palette.text));
+    visuals.selection.bg_fill = egui_color(palette.primary);
+    visuals.hyperlink_color = egui_color(palette.primary);
+    visuals.warn_fg_color = egui_color(palette.suspicious);
+    visuals.error_fg_color = egui_color(palette.suspicious);
+    ctx.set_visuals(visuals);
+}
+
+fn egui_shortcut(combo: &KeyCombo) -> Option<(egui::Modifiers, egui::Key)> {
+    let key = egui::Key::ALL
+        .iter()
+        .find(|key| key.name().eq_ignore_ascii_case(&combo.key))?;
+    let modifiers = egui::Modifiers {
+        alt: combo.alt,
+        ctrl: combo.ctrl,
+        shift: combo.shift,
+        command: combo.ctrl,
+        ..Default::default()
+    };
+    Some((modifiers, *key))
+}
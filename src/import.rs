@@ -0,0 +1,252 @@
+// Import of recorded channel values from the CSV and JSON files written by `export`.
+// Only `channel` and `value` are read, other columns are ignored, so exported snapshots
+// and hand-written files both work.
+
+use std::fmt;
+use std::fs::{self, File};
+use std::io;
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::channel_value::{ChannelValue, ValueKind};
+use crate::export::ExportFormat;
+
+#[derive(Debug, Deserialize)]
+struct ImportedRow {
+    channel: usize,
+    /// Number or text as written by `export`, parsed according to the session's value kind.
+    value: serde_json::Value,
+}
+
+impl ImportedRow {
+    fn value_text(&self) -> String {
+        match &self.value {
+            serde_json::Value::String(text) => text.clone(),
+            serde_json::Value::Array(fields) => fields
+                .iter()
+                .map(|field| field.to_string())
+                .collect::<Vec<_>>()
+                .join(";"),
+            other => other.to_string(),
+        }
+    }
+}
+
+/// Problem with a single row, rows are numbered from 1 in the order they appear in the file.
+#[derive(Debug)]
+pub struct RowError {
+    pub row: usize,
+    pub message: String,
+}
+
+impl fmt::Display for RowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "row {}: {}", self.row, self.message)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    /// Valid `(channel index, value)` pairs, indices start from 0.
+    pub values: Vec<(usize, ChannelValue)>,
+    pub errors: Vec<RowError>,
+}
+
+impl ImportReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Reads channel values from `path` and checks them against the channel count and value range.
+///
+/// Fails only if the file itself can't be read or parsed, problems with single rows end up in
+/// `ImportReport::errors`.
+pub fn import_channels(
+    path: &Path,
+    format: ExportFormat,
+    channels_count: usize,
+    value_kind: &ValueKind,
+    value_range: RangeInclusive<f64>,
+) -> io::Result<ImportReport> {
+    let rows: Vec<Result<ImportedRow, String>> = match format {
+        ExportFormat::Csv => csv::Reader::from_reader(File::open(path)?)
+            .deserialize()
+            .map(|row| row.map_err(|err| err.to_string()))
+            .collect(),
+        ExportFormat::Json => {
+            let document: serde_json::Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+            // Either a whole exported snapshot or just the array of channels
+            let channels = match document {
+                serde_json::Value::Object(mut snapshot) => snapshot
+                    .remove("channels")
+                    .unwrap_or(serde_json::Value::Null),
+                other => other,
+            };
+            let serde_json::Value::Array(channels) = channels else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "expected an array of channels",
+                ));
+            };
+            channels
+                .into_iter()
+                .map(|row| serde_json::from_value(row).map_err(|err| err.to_string()))
+                .collect()
+        }
+    };
+
+    let mut report = ImportReport::default();
+    let mut seen_channels = vec![false; channels_count];
+    for (position, row) in rows.into_iter().enumerate() {
+        let checked = row.and_then(|row| {
+            if row.channel == 0 || row.channel > channels_count {
+                Err(format!(
+                    "channel {} is outside 1..={channels_count}",
+                    row.channel
+                ))
+            } else if seen_channels[row.channel - 1] {
+                Err(format!("channel {} appears more than once", row.channel))
+            } else {
+                let text = row.value_text();
+                let value = value_kind
+                    .parse_in_range(&text, value_range.clone())
+                    .map_err(|err| format!("value {text}: {err}"))?;
+                seen_channels[row.channel - 1] = true;
+                Ok((row.channel - 1, value))
+            }
+        });
+
+        match checked {
+            Ok(value) => report.values.push(value),
+            Err(message) => report.errors.push(RowError {
+                row: position + 1,
+                message,
+            }),
+        }
+    }
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::TempDir;
+
+    // Imports `contents` for 3 unsigned channels from a file named after the test
+    fn import(name: &str, format: ExportFormat, contents: &str) -> ImportReport {
+        let temp_dir = TempDir::new(name);
+        let path = temp_dir.file(&format!("import.{}", format.extension()));
+        fs::write(&path, contents).unwrap();
+        let kind = ValueKind::Unsigned;
+        import_channels(&path, format, 3, &kind, kind.range()).unwrap()
+    }
+
+    fn error_messages(report: &ImportReport) -> Vec<String> {
+        report.errors.iter().map(RowError::to_string).collect()
+    }
+
+    #[test]
+    fn csv_rows_become_channel_values() {
+        let report = import(
+            "csv",
+            ExportFormat::Csv,
+            "channel,value,suspicious\n3,90,true\n1,10,false\n",
+        );
+        assert!(report.is_valid());
+        assert_eq!(
+            report.values,
+            [
+                (2, ChannelValue::Unsigned(90)),
+                (0, ChannelValue::Unsigned(10))
+            ]
+        );
+    }
+
+    #[test]
+    fn channels_outside_the_channel_set_are_rejected() {
+        let report = import(
+            "outside",
+            ExportFormat::Csv,
+            "channel,value\n0,10\n4,10\n2,10\n",
+        );
+        assert_eq!(
+            error_messages(&report),
+            [
+                "row 1: channel 0 is outside 1..=3",
+                "row 2: channel 4 is outside 1..=3"
+            ]
+        );
+        assert_eq!(report.values, [(1, ChannelValue::Unsigned(10))]);
+    }
+
+    #[test]
+    fn duplicate_channels_are_rejected() {
+        let report = import(
+            "duplicate",
+            ExportFormat::Csv,
+            "channel,value\n2,10\n2,20\n",
+        );
+        assert_eq!(
+            error_messages(&report),
+            ["row 2: channel 2 appears more than once"]
+        );
+        assert_eq!(report.values, [(1, ChannelValue::Unsigned(10))]);
+    }
+
+    #[test]
+    fn values_outside_the_range_are_rejected() {
+        let report = import(
+            "range",
+            ExportFormat::Csv,
+            "channel,value\n1,101\n2,abc\n3,100\n",
+        );
+        assert_eq!(
+            error_messages(&report),
+            [
+                "row 1: value 101: must be between 1 and 100",
+                "row 2: value abc: not a valid number"
+            ]
+        );
+        // A rejected row doesn't count as seen
+        let report = import(
+            "range_retry",
+            ExportFormat::Csv,
+            "channel,value\n1,0\n1,5\n",
+        );
+        assert_eq!(report.values, [(0, ChannelValue::Unsigned(5))]);
+    }
+
+    #[test]
+    fn json_snapshots_and_bare_arrays_are_both_read() {
+        let snapshot = import(
+            "snapshot",
+            ExportFormat::Json,
+            r#"{"exported_at_ms": 0, "channels": [{"channel": 1, "value": 42, "rule": null}]}"#,
+        );
+        let array = import(
+            "array",
+            ExportFormat::Json,
+            r#"[{"channel": 1, "value": "42"}]"#,
+        );
+        assert_eq!(snapshot.values, [(0, ChannelValue::Unsigned(42))]);
+        assert_eq!(array.values, snapshot.values);
+
+        let report = import("bad_row", ExportFormat::Json, r#"[{"value": 1}]"#);
+        assert_eq!(report.errors.len(), 1);
+    }
+
+    #[test]
+    fn json_without_channels_fails() {
+        let temp_dir = TempDir::new("import_empty");
+        let path = temp_dir.file("import.json");
+        fs::write(&path, r#"{"history": []}"#).unwrap();
+        let kind = ValueKind::Unsigned;
+        let err = import_channels(&path, ExportFormat::Json, 3, &kind, kind.range()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,439 @@
+// Keyboard shortcuts shared by both front ends.
+// Keys are identified by name ("1", "Left", "Tab", "Delete", ...) so each front end only has to
+// turn its own key events into a `KeyCombo` and ask `KeyBindings` what to do with it.
+
+use std::fmt;
+
+use crate::common::ChannelNumber;
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const KEY_BINDINGS_FLAG: &str = "--key-bindings";
+pub const KEY_BINDINGS_ENV: &str = "KEY_BINDINGS";
+/// Keys that can be bound, named as egui names them and in lowercase.
+pub const KEY_NAMES: &[&str] = &[
+    "0",
+    "1",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "f1",
+    "f2",
+    "f3",
+    "f4",
+    "f5",
+    "f6",
+    "f7",
+    "f8",
+    "f9",
+    "f10",
+    "f11",
+    "f12",
+    "f13",
+    "f14",
+    "f15",
+    "f16",
+    "f17",
+    "f18",
+    "f19",
+    "f20",
+    "f21",
+    "f22",
+    "f23",
+    "f24",
+    "left",
+    "right",
+    "up",
+    "down",
+    "escape",
+    "tab",
+    "backspace",
+    "enter",
+    "insert",
+    "delete",
+    "home",
+    "end",
+    "pageup",
+    "pagedown",
+    "space",
+    "colon",
+    "comma",
+    "minus",
+    "period",
+    "plus",
+    "equals",
+    "semicolon",
+    "backslash",
+    "slash",
+    "openbracket",
+    "closebracket",
+    "backtick",
+    "quote",
+    "copy",
+    "cut",
+    "paste",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct KeyCombo {
+    /// Lowercase key name, e.g. "1", "left" or "tab".
+    pub key: String,
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl KeyCombo {
+    pub fn new(key_name: &str, ctrl: bool, shift: bool, alt: bool) -> Self {
+        KeyCombo {
+            key: key_name.to_ascii_lowercase(),
+            ctrl,
+            shift,
+            alt,
+        }
+    }
+
+    /// Parses combos like `ctrl+tab`, `shift+left` or `delete`, the key has to be one of
+    /// `KEY_NAMES`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut combo = KeyCombo::new("", false, false, false);
+        for part in text.split('+').map(str::trim) {
+            match part.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => combo.ctrl = true,
+                "shift" => combo.shift = true,
+                "alt" => combo.alt = true,
+                "" => return None,
+                key if combo.key.is_empty() && KEY_NAMES.contains(&key) => {
+                    combo.key = key.to_string()
+                }
+                _ => return None,
+            }
+        }
+        (!combo.key.is_empty()).then_some(combo)
+    }
+}
+
+impl fmt::Display for KeyCombo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.alt {
+            write!(f, "Alt+")?;
+        }
+        let mut letters = self.key.chars();
+        if let Some(first) = letters.next() {
+            write!(f, "{}{}", first.to_ascii_uppercase(), letters.as_str())?;
+        }
+        Ok(())
+    }
+}
+
+/// What a shortcut does, front ends ignore actions they have no use for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShortcutAction {
+    SelectChannel(ChannelNumber),
+    ChangeChannel(i32),
+    NextTab,
+    ClearCurrentRow,
+    CloseModal,
+    OpenUserInfo,
+    SaveSettings,
+    Undo,
+    Redo,
+}
+
+impl ShortcutAction {
+    /// Parses the names used in `--key-bindings`, e.g. `channel:3` or `next-tab`.
+    pub fn parse(text: &str) -> Option<Self> {
+        let (name, argument) = text.trim().split_once(':').unwrap_or((text.trim(), ""));
+        let action = match name {
+            "channel" => ShortcutAction::SelectChannel(argument.parse().ok()?),
+            "change-channel" => ShortcutAction::ChangeChannel(argument.parse().ok()?),
+            "next-tab" => ShortcutAction::NextTab,
+            "clear-current" => ShortcutAction::ClearCurrentRow,
+            "close-modal" => ShortcutAction::CloseModal,
+            "open-user-info" => ShortcutAction::OpenUserInfo,
+            "save-settings" => ShortcutAction::SaveSettings,
+            "undo" => ShortcutAction::Undo,
+            "redo" => ShortcutAction::Redo,
+            _ => return None,
+        };
+        Some(action)
+    }
+}
+
+impl fmt::Display for ShortcutAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShortcutAction::SelectChannel(channel) => write!(f, "Select channel {channel}"),
+            ShortcutAction::ChangeChannel(change) if *change < 0 => {
+                write!(f, "Go {} channel(s) back", change.unsigned_abs())
+            }
+            ShortcutAction::ChangeChannel(change) => write!(f, "Go {change} channel(s) forward"),
+            ShortcutAction::NextTab => write!(f, "Next tab"),
+            ShortcutAction::ClearCurrentRow => write!(f, "Clear current channel"),
+            ShortcutAction::CloseModal => write!(f, "Close dialog"),
+            ShortcutAction::OpenUserInfo => write!(f, "Open user info"),
+            ShortcutAction::SaveSettings => write!(f, "Save settings"),
+            ShortcutAction::Undo => write!(f, "Undo"),
+            ShortcutAction::Redo => write!(f, "Redo"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct KeyBindings {
+    bindings: Vec<(KeyCombo, ShortcutAction)>,
+}
+
+impl KeyBindings {
+    /// Defaults of the retained-mode app: digits select channels 1-9, arrows move between
+    /// channels, Ctrl+Tab cycles tabs, Delete clears the current channel and Ctrl+Z/Ctrl+Y
+    /// undo and redo.
+    #[cfg(feature = "retained-mode")]
+    pub fn retained_defaults() -> Self {
+        let mut bindings: Vec<_> = (1..=9)
+            .map(|channel| {
+                (
+                    KeyCombo::new(&channel.to_string(), false, false, false),
+                    ShortcutAction::SelectChannel(channel),
+                )
+            })
+            .collect();
+        bindings.extend([
+            (
+                KeyCombo::new("left", false, false, false),
+                ShortcutAction::ChangeChannel(-1),
+            ),
+            (
+                KeyCombo::new("right", false, false, false),
+                ShortcutAction::ChangeChannel(1),
+            ),
+            (
+                KeyCombo::new("tab", true, false, false),
+                ShortcutAction::NextTab,
+            ),
+            (
+                KeyCombo::new("delete", false, false, false),
+                ShortcutAction::ClearCurrentRow,
+            ),
+            (KeyCombo::new("z", true, false, false), ShortcutAction::Undo),
+            (KeyCombo::new("y", true, false, false), ShortcutAction::Redo),
+        ]);
+        KeyBindings { bindings }
+    }
+
+    /// Defaults of the immediate-mode app: Ctrl+Tab cycles tabs, Escape closes dialogs,
+    /// Ctrl+I opens the user info and Ctrl+S saves the settings.
+    #[cfg(feature = "immediate-mode")]
+    pub fn immediate_defaults() -> Self {
+        KeyBindings {
+            bindings: vec![
+                (
+                    KeyCombo::new("tab", true, false, false),
+                    ShortcutAction::NextTab,
+                ),
+                (
+                    KeyCombo::new("escape", false, false, false),
+                    ShortcutAction::CloseModal,
+                ),
+                (
+                    KeyCombo::new("i", true, false, false),
+                    ShortcutAction::OpenUserInfo,
+                ),
+                (
+                    KeyCombo::new("s", true, false, false),
+                    ShortcutAction::SaveSettings,
+                ),
+            ],
+        }
+    }
+
+    pub fn bindings(&self) -> &[(KeyCombo, ShortcutAction)] {
+        &self.bindings
+    }
+
+    #[cfg(feature = "retained-mode")]
+    pub fn action_for(&self, combo: &KeyCombo) -> Option<&ShortcutAction> {
+        self.bindings
+            .iter()
+            .find(|(bound_combo, _)| bound_combo == combo)
+            .map(|(_, action)| action)
+    }
+
+    /// Applies overrides like `ctrl+right=next-tab; delete=none`, where `none` removes a binding.
+    /// A key combination may appear only once in `spec`.
+    pub fn apply_overrides(&mut self, spec: &str) -> Result<(), String> {
+        let mut overridden = Vec::new();
+        for entry in spec.split([';', '\n']).map(str::trim) {
+            if entry.is_empty() {
+                continue;
+            }
+
+            let (combo, action) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("'{entry}': expected <keys>=<action>"))?;
+            let combo =
+                KeyCombo::parse(combo).ok_or_else(|| format!("'{entry}': bad key combination"))?;
+            if overridden.contains(&combo) {
+                return Err(format!("'{entry}': key combination bound twice"));
+            }
+            overridden.push(combo.clone());
+
+            self.bindings
+                .retain(|(bound_combo, _)| bound_combo != &combo);
+            if action.trim() != "none" {
+                let action = ShortcutAction::parse(action)
+                    .ok_or_else(|| format!("'{entry}': unknown action"))?;
+                self.bindings.push((combo, action));
+            }
+        }
+        Ok(())
+    }
+
+    /// `defaults` with the overrides from `--key-bindings` or `KEY_BINDINGS` applied.
+    pub fn from_startup_args(mut defaults: KeyBindings) -> Self {
+        if let Some(spec) = startup_option(KEY_BINDINGS_FLAG, KEY_BINDINGS_ENV) {
+            let fallback = defaults.clone();
+            if let Err(err) = defaults.apply_overrides(&spec) {
+                eprintln!("Invalid key binding {err}. Using the default key bindings.");
+                return fallback;
+            }
+        }
+        defaults
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bindings_with(spec: &str) -> Result<KeyBindings, String> {
+        let mut key_bindings = KeyBindings {
+            bindings: vec![(
+                KeyCombo::new("delete", false, false, false),
+                ShortcutAction::ClearCurrentRow,
+            )],
+        };
+        key_bindings.apply_overrides(spec)?;
+        Ok(key_bindings)
+    }
+
+    #[test]
+    fn parsing_key_combos() {
+        assert_eq!(
+            KeyCombo::parse("Ctrl + Shift+Right"),
+            Some(KeyCombo::new("right", true, true, false))
+        );
+        assert_eq!(
+            KeyCombo::parse("control+alt+openbracket"),
+            Some(KeyCombo::new("openbracket", true, false, true))
+        );
+        for bad_combo in ["", "ctrl", "ctrl+", "a+b", "ctrl+volumeup", "f25"] {
+            assert_eq!(KeyCombo::parse(bad_combo), None, "{bad_combo}");
+        }
+    }
+
+    #[test]
+    fn parsing_actions() {
+        assert_eq!(
+            ShortcutAction::parse(" channel:3 "),
+            Some(ShortcutAction::SelectChannel(3))
+        );
+        assert_eq!(
+            ShortcutAction::parse("change-channel:-2"),
+            Some(ShortcutAction::ChangeChannel(-2))
+        );
+        assert_eq!(
+            ShortcutAction::parse("next-tab"),
+            Some(ShortcutAction::NextTab)
+        );
+        for bad_action in ["channel", "channel:x", "change-channel:", "jump", ""] {
+            assert_eq!(ShortcutAction::parse(bad_action), None, "{bad_action}");
+        }
+    }
+
+    #[test]
+    fn overrides_add_replace_and_remove_bindings() {
+        let key_bindings =
+            bindings_with("ctrl+right=next-tab;\n delete=none; ; 0=channel:10").unwrap();
+        assert_eq!(
+            key_bindings.bindings(),
+            [
+                (
+                    KeyCombo::new("right", true, false, false),
+                    ShortcutAction::NextTab
+                ),
+                (
+                    KeyCombo::new("0", false, false, false),
+                    ShortcutAction::SelectChannel(10)
+                ),
+            ]
+        );
+
+        let key_bindings = bindings_with("delete=next-tab").unwrap();
+        assert_eq!(
+            key_bindings.bindings(),
+            [(
+                KeyCombo::new("delete", false, false, false),
+                ShortcutAction::NextTab
+            )]
+        );
+    }
+
+    #[test]
+    fn bad_overrides_are_rejected() {
+        assert_eq!(
+            bindings_with("delete").unwrap_err(),
+            "'delete': expected <keys>=<action>"
+        );
+        assert_eq!(
+            bindings_with("ctrl+volumeup=next-tab").unwrap_err(),
+            "'ctrl+volumeup=next-tab': bad key combination"
+        );
+        assert_eq!(
+            bindings_with("delete=jump").unwrap_err(),
+            "'delete=jump': unknown action"
+        );
+        assert_eq!(
+            bindings_with("ctrl+z=next-tab; Ctrl+Z=clear-current").unwrap_err(),
+            "'Ctrl+Z=clear-current': key combination bound twice"
+        );
+    }
+}
@@ -1,10 +1,54 @@
+#[cfg(feature = "retained-mode")]
+mod action_bar;
+#[cfg(feature = "retained-mode")]
+mod app_config;
+mod app_theme;
+#[cfg(feature = "retained-mode")]
+mod channel_data_source;
+#[cfg(feature = "retained-mode")]
+mod channel_history;
+#[cfg(feature = "retained-mode")]
+mod channel_metadata;
+#[cfg(feature = "retained-mode")]
+mod channel_model;
+#[cfg(feature = "retained-mode")]
+mod channel_overview;
+#[cfg(feature = "retained-mode")]
+mod channel_statistics;
+#[cfg(feature = "retained-mode")]
+mod channel_value;
 mod common;
+#[cfg(feature = "retained-mode")]
+mod comparison_table;
+#[cfg(feature = "retained-mode")]
+mod event_log;
+#[cfg(feature = "retained-mode")]
+mod export;
+#[cfg(feature = "retained-mode")]
+mod import;
 
 #[cfg(feature = "immediate-mode")]
 mod immediate_mode_app;
+mod key_bindings;
+#[cfg(feature = "retained-mode")]
+mod message_recorder;
 
+#[cfg(feature = "retained-mode")]
+mod navigation;
+mod notifications;
 #[cfg(feature = "retained-mode")]
 mod retained_mode_app;
+#[cfg(feature = "retained-mode")]
+mod session;
+mod startup_args;
+#[cfg(feature = "retained-mode")]
+mod suspicious_rules;
+#[cfg(test)]
+mod test_util;
+#[cfg(feature = "retained-mode")]
+mod toast_overlay;
+#[cfg(feature = "retained-mode")]
+mod undo_history;
 
 // TODOS
 // add scripts for both modes
@@ -0,0 +1,187 @@
+// Recording of the messages handled by the retained-mode app, and their replay.
+// A recording is a JSON-lines file: the first line holds the initial state, every further
+// line one message with the time it was handled, so a crashed session still leaves a
+// usable file behind.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::channel_metadata::ChannelMetadata;
+use crate::channel_value::{ChannelValue, ValueKind};
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const RECORD_FLAG: &str = "--record";
+pub const RECORD_ENV: &str = "CHANNEL_RECORD";
+pub const REPLAY_FLAG: &str = "--replay";
+pub const REPLAY_ENV: &str = "CHANNEL_REPLAY";
+pub const REPLAY_MODE_FLAG: &str = "--replay-mode";
+pub const REPLAY_MODE_ENV: &str = "CHANNEL_REPLAY_MODE";
+
+/// State of the app when the recording started, channels are numbered from 1 as in the UI.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InitialState {
+    pub values: Vec<ChannelValue>,
+    pub suspicious_limit: f64,
+    /// Recordings made before values had kinds hold unsigned values.
+    #[serde(default)]
+    pub value_kind: ValueKind,
+    pub previous_channel: Option<usize>,
+    pub current_channel: Option<usize>,
+    #[serde(default)]
+    pub channel_metadata: Vec<ChannelMetadata>,
+    /// Seed of the data source, for information only as samples are recorded as messages.
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedMessage<M> {
+    /// Milliseconds since the recording started.
+    pub at_ms: u64,
+    pub message: M,
+}
+
+pub struct MessageRecorder {
+    writer: BufWriter<File>,
+    started: Instant,
+}
+
+impl MessageRecorder {
+    pub fn create(path: &Path, initial_state: &InitialState) -> io::Result<Self> {
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(&mut writer, initial_state)?;
+        writeln!(writer)?;
+        writer.flush()?;
+        Ok(MessageRecorder {
+            writer,
+            started: Instant::now(),
+        })
+    }
+
+    pub fn record<M: Serialize>(&mut self, message: &M) -> io::Result<()> {
+        let recorded = RecordedMessage {
+            at_ms: self.started.elapsed().as_millis() as u64,
+            message,
+        };
+        serde_json::to_writer(&mut self.writer, &recorded)?;
+        writeln!(self.writer)?;
+        self.writer.flush()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Recording<M> {
+    pub initial_state: InitialState,
+    pub messages: Vec<RecordedMessage<M>>,
+}
+
+impl<M: DeserializeOwned> Recording<M> {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        let initial_state =
+            serde_json::from_str(lines.next().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData, "recording is empty")
+            })?)?;
+        let messages = lines
+            .map(serde_json::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Recording {
+            initial_state,
+            messages,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayMode {
+    /// Messages are handled with the same timing as when they were recorded.
+    RealTime,
+    /// All messages are handled right after startup.
+    MaxSpeed,
+    /// No window, the final state can be exported with `--export`.
+    Headless,
+}
+
+/// Messages of a recording waiting to be handled.
+pub struct Replay<M> {
+    pending: VecDeque<RecordedMessage<M>>,
+    mode: ReplayMode,
+    started: Instant,
+}
+
+impl<M> Replay<M> {
+    pub fn new(messages: Vec<RecordedMessage<M>>, mode: ReplayMode) -> Self {
+        Replay {
+            pending: messages.into(),
+            mode,
+            started: Instant::now(),
+        }
+    }
+
+    pub fn mode(&self) -> ReplayMode {
+        self.mode
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Messages due by now, all remaining ones unless replaying in real time.
+    pub fn due_messages(&mut self) -> Vec<M> {
+        let elapsed_ms = self.started.elapsed().as_millis() as u64;
+        let mut due = Vec::new();
+        while let Some(recorded) = self.pending.front() {
+            if self.mode == ReplayMode::RealTime && recorded.at_ms > elapsed_ms {
+                break;
+            }
+            due.extend(self.pending.pop_front().map(|recorded| recorded.message));
+        }
+        due
+    }
+
+    /// All remaining messages at once, whatever the mode.
+    pub fn drain_all(&mut self) -> Vec<M> {
+        self.pending
+            .drain(..)
+            .map(|recorded| recorded.message)
+            .collect()
+    }
+}
+
+/// File to record to, from `--record` or `CHANNEL_RECORD`.
+pub fn record_path_from_startup_args() -> Option<PathBuf> {
+    startup_option(RECORD_FLAG, RECORD_ENV).map(PathBuf::from)
+}
+
+/// Recording to replay from `--replay` or `CHANNEL_REPLAY`, with the mode from
+/// `--replay-mode` or `CHANNEL_REPLAY_MODE` (`real-time`, `max-speed` or `headless`).
+pub fn replay_from_startup_args() -> Option<(PathBuf, ReplayMode)> {
+    let path = startup_option(REPLAY_FLAG, REPLAY_ENV).map(PathBuf::from)?;
+    let mode = match startup_option(REPLAY_MODE_FLAG, REPLAY_MODE_ENV).as_deref() {
+        None | Some("real-time") => ReplayMode::RealTime,
+        Some("max-speed") => ReplayMode::MaxSpeed,
+        Some("headless") => ReplayMode::Headless,
+        Some(other) => {
+            eprintln!("Unknown replay mode '{other}', replaying in real time.");
+            ReplayMode::RealTime
+        }
+    };
+    Some((path, mode))
+}
@@ -0,0 +1,240 @@
+// Browser-style history of visited channels.
+// The Previous/Current rows of the table are the entry under the cursor and the one before it.
+
+//*  Constants */
+pub const MAX_NAVIGATION_ENTRIES: usize = 100;
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct NavigationHistory {
+    /// Visited channel indices, oldest first.
+    entries: Vec<usize>,
+    /// Position of the current channel in `entries`, `None` when nothing is selected.
+    /// One past the last entry once the current channel was cleared.
+    cursor: Option<usize>,
+}
+
+impl NavigationHistory {
+    /// History holding just `previous` and `current`, either of them may be missing.
+    pub fn with_selection(previous: Option<usize>, current: Option<usize>) -> Self {
+        let previous = previous.filter(|&previous| Some(previous) != current);
+        let entries: Vec<_> = previous.into_iter().chain(current).collect();
+        let cursor = match current {
+            Some(_) => entries.len().checked_sub(1),
+            None => (!entries.is_empty()).then_some(entries.len()),
+        };
+        NavigationHistory { entries, cursor }
+    }
+
+    pub fn entries(&self) -> &[usize] {
+        &self.entries
+    }
+
+    pub fn cursor(&self) -> Option<usize> {
+        self.cursor
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.cursor
+            .and_then(|cursor| self.entries.get(cursor))
+            .copied()
+    }
+
+    pub fn previous(&self) -> Option<usize> {
+        self.cursor
+            .and_then(|cursor| cursor.checked_sub(1))
+            .map(|position| self.entries[position])
+    }
+
+    pub fn can_go_back(&self) -> bool {
+        self.cursor.is_some_and(|cursor| cursor > 0)
+    }
+
+    pub fn can_go_forward(&self) -> bool {
+        match self.cursor {
+            Some(cursor) => cursor + 1 < self.entries.len(),
+            None => !self.entries.is_empty(),
+        }
+    }
+
+    /// Selects `channel_index`, dropping any entries ahead of the cursor like a browser does.
+    pub fn visit(&mut self, channel_index: usize) {
+        if self.current() == Some(channel_index) {
+            return;
+        }
+
+        self.entries
+            .truncate(self.cursor.map_or(0, |cursor| cursor + 1));
+        self.entries.push(channel_index);
+        if self.entries.len() > MAX_NAVIGATION_ENTRIES {
+            self.entries.remove(0);
+        }
+        self.cursor = Some(self.entries.len() - 1);
+    }
+
+    pub fn back(&mut self) {
+        if self.can_go_back() {
+            self.cursor = self.cursor.map(|cursor| cursor - 1);
+        }
+    }
+
+    pub fn forward(&mut self) {
+        if self.can_go_forward() {
+            self.cursor = Some(self.cursor.map_or(0, |cursor| cursor + 1));
+        }
+    }
+
+    pub fn jump_to(&mut self, position: usize) {
+        if position < self.entries.len() {
+            self.cursor = Some(position);
+        }
+    }
+
+    /// Clears the current channel and keeps the previous one. The current entry and the ones
+    /// ahead of it are dropped, as a new visit would.
+    pub fn remove_current(&mut self) {
+        if let Some(cursor) = self.cursor.filter(|&cursor| cursor < self.entries.len()) {
+            self.entries.truncate(cursor);
+            self.cursor = (cursor > 0).then_some(cursor);
+        }
+    }
+
+    /// Removes the entry before the current one.
+    pub fn remove_previous(&mut self) {
+        if let Some(cursor) = self.cursor.filter(|&cursor| cursor > 0) {
+            self.entries.remove(cursor - 1);
+            self.cursor = (!self.entries.is_empty()).then_some(cursor - 1);
+        }
+    }
+
+    /// Drops entries for channels that no longer exist, keeping the cursor on the same visit
+    /// when it survives.
+    pub fn retain_channels(&mut self, channels_count: usize) {
+        let is_current_cleared = self.cursor == Some(self.entries.len());
+        let mut kept_before_cursor = 0;
+        let mut cursor_kept = false;
+        let mut position = 0;
+        self.entries.retain(|&channel_index| {
+            let keep = channel_index < channels_count;
+            if let Some(cursor) = self.cursor {
+                if keep && position < cursor {
+                    kept_before_cursor += 1;
+                }
+                if position == cursor {
+                    cursor_kept = keep;
+                }
+            }
+            position += 1;
+            keep
+        });
+
+        self.cursor = match self.cursor {
+            Some(_) if is_current_cleared => {
+                (!self.entries.is_empty()).then_some(self.entries.len())
+            }
+            Some(_) if cursor_kept => Some(kept_before_cursor),
+            Some(_) => kept_before_cursor.checked_sub(1),
+            None => None,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn visited(channels: &[usize]) -> NavigationHistory {
+        let mut navigation = NavigationHistory::default();
+        for &channel in channels {
+            navigation.visit(channel);
+        }
+        navigation
+    }
+
+    fn selection(navigation: &NavigationHistory) -> (Option<usize>, Option<usize>) {
+        (navigation.previous(), navigation.current())
+    }
+
+    #[test]
+    fn visits_drop_the_entries_ahead_of_the_cursor() {
+        let mut navigation = visited(&[1, 2, 3]);
+        navigation.back();
+        navigation.back();
+        navigation.visit(5);
+        assert_eq!(navigation.entries(), [1, 5]);
+        assert!(!navigation.can_go_forward());
+
+        // Visiting the current channel again adds nothing
+        navigation.visit(5);
+        assert_eq!(navigation.entries(), [1, 5]);
+    }
+
+    #[test]
+    fn back_and_forward_stop_at_both_ends() {
+        let mut navigation = visited(&[1, 2]);
+        navigation.forward();
+        assert_eq!(selection(&navigation), (Some(1), Some(2)));
+
+        navigation.back();
+        navigation.back();
+        assert_eq!(selection(&navigation), (None, Some(1)));
+        assert!(!navigation.can_go_back());
+
+        navigation.forward();
+        assert_eq!(navigation.current(), Some(2));
+        assert_eq!(navigation.cursor(), Some(1));
+    }
+
+    #[test]
+    fn clearing_the_current_channel_keeps_the_previous_one() {
+        let mut navigation = visited(&[1, 2, 3]);
+        navigation.back();
+        navigation.remove_current();
+        assert_eq!(selection(&navigation), (Some(1), None));
+        assert_eq!(navigation.entries(), [1]);
+
+        // Back returns to the previous channel, the next visit follows it
+        let mut went_back = navigation.clone();
+        went_back.back();
+        assert_eq!(selection(&went_back), (None, Some(1)));
+        navigation.visit(4);
+        assert_eq!(selection(&navigation), (Some(1), Some(4)));
+    }
+
+    #[test]
+    fn shrinking_keeps_the_surviving_entries() {
+        let mut navigation = visited(&[1, 6, 2, 7]);
+        navigation.back();
+        navigation.retain_channels(5);
+        assert_eq!(navigation.entries(), [1, 2]);
+        assert_eq!(selection(&navigation), (Some(1), Some(2)));
+
+        // The current channel is gone, the one before it takes over
+        let mut navigation = visited(&[1, 6]);
+        navigation.retain_channels(5);
+        assert_eq!(selection(&navigation), (None, Some(1)));
+
+        // A cleared current channel stays cleared
+        let mut navigation = visited(&[1, 6, 2]);
+        navigation.remove_current();
+        navigation.retain_channels(5);
+        assert_eq!(selection(&navigation), (Some(1), None));
+    }
+
+    #[test]
+    fn histories_from_a_saved_selection() {
+        let selections = [
+            (None, None),
+            (Some(1), None),
+            (None, Some(2)),
+            (Some(1), Some(2)),
+        ];
+        for (previous, current) in selections {
+            let navigation = NavigationHistory::with_selection(previous, current);
+            assert_eq!(selection(&navigation), (previous, current));
+        }
+        assert_eq!(
+            selection(&NavigationHistory::with_selection(Some(3), Some(3))),
+            (None, Some(3))
+        );
+    }
+}
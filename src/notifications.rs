@@ -0,0 +1,266 @@
+// Toast notifications shared by both front ends.
+// Toasts stack up in a corner of the window and go away on their own after a time that depends
+// on their severity. Everything that was shown stays in a history the user can look back at,
+// each front end only decides how toasts and the history are drawn.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::app_theme::{Palette, Rgb};
+
+//*  Constants */
+/// Toasts shown at once, older ones are dismissed to make room for new ones.
+pub const MAX_VISIBLE_TOASTS: usize = 4;
+pub const MAX_HISTORY_ENTRIES: usize = 100;
+pub const INFO_TIMEOUT_SECS: u64 = 4;
+pub const WARNING_TIMEOUT_SECS: u64 = 8;
+/// Width of a toast in both front ends.
+pub const TOAST_WIDTH: f32 = 320.0;
+/// Distance of the toasts from the window edges.
+pub const TOAST_MARGIN: f32 = 15.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// How long a toast stays up, errors stay until they are dismissed.
+    pub fn timeout(&self) -> Option<Duration> {
+        match self {
+            Severity::Info | Severity::Success => Some(Duration::from_secs(INFO_TIMEOUT_SECS)),
+            Severity::Warning => Some(Duration::from_secs(WARNING_TIMEOUT_SECS)),
+            Severity::Error => None,
+        }
+    }
+
+    pub fn color(&self, palette: &Palette) -> Rgb {
+        match self {
+            Severity::Info => palette.primary,
+            Severity::Success => palette.normal,
+            Severity::Warning | Severity::Error => palette.suspicious,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Severity::Info => "Info",
+            Severity::Success => "Success",
+            Severity::Warning => "Warning",
+            Severity::Error => "Error",
+        };
+        write!(f, "{name}")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Toast {
+    pub id: u64,
+    pub severity: Severity,
+    pub text: String,
+    /// Wall-clock time for the history, `shown_at` decides when the toast expires.
+    pub created: SystemTime,
+    pub shown_at: Instant,
+}
+
+impl Toast {
+    fn expires_at(&self) -> Option<Instant> {
+        self.severity
+            .timeout()
+            .map(|timeout| self.shown_at + timeout)
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Notifications {
+    /// Toasts on screen, oldest first.
+    visible: VecDeque<Toast>,
+    /// Every toast that was shown, oldest first.
+    history: VecDeque<Toast>,
+    next_id: u64,
+}
+
+impl Notifications {
+    /// Shows a new toast and returns its id.
+    pub fn push(&mut self, severity: Severity, text: impl Into<String>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let toast = Toast {
+            id,
+            severity,
+            text: text.into(),
+            created: SystemTime::now(),
+            shown_at: Instant::now(),
+        };
+
+        if self.history.len() == MAX_HISTORY_ENTRIES {
+            self.history.pop_front();
+        }
+        self.history.push_back(toast.clone());
+        if self.visible.len() == MAX_VISIBLE_TOASTS {
+            self.visible.pop_front();
+        }
+        self.visible.push_back(toast);
+        id
+    }
+
+    /// Like `push`, but keeps a toast with the same severity and text that is still on screen
+    /// instead of stacking a copy of it. Returns the id of the toast on screen.
+    pub fn push_unique(&mut self, severity: Severity, text: impl Into<String>) -> u64 {
+        let text = text.into();
+        match self
+            .visible
+            .iter()
+            .find(|toast| toast.severity == severity && toast.text == text)
+        {
+            Some(toast) => toast.id,
+            None => self.push(severity, text),
+        }
+    }
+
+    /// Puts new text into toast `id` and restarts its timeout while it's on screen, shows a new
+    /// toast otherwise. A replaced toast keeps a single history entry with its latest text.
+    /// Returns the id of the toast on screen.
+    #[cfg(feature = "retained-mode")]
+    pub fn replace(&mut self, id: Option<u64>, severity: Severity, text: impl Into<String>) -> u64 {
+        let Some(toast) = id.and_then(|id| self.visible.iter_mut().find(|toast| toast.id == id))
+        else {
+            return self.push(severity, text);
+        };
+        toast.severity = severity;
+        toast.text = text.into();
+        toast.created = SystemTime::now();
+        toast.shown_at = Instant::now();
+        let toast = toast.clone();
+        if let Some(entry) = self.history.iter_mut().find(|entry| entry.id == toast.id) {
+            *entry = toast.clone();
+        }
+        toast.id
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.visible.retain(|toast| toast.id != id);
+    }
+
+    /// Removes the toasts that timed out by `now`, returns whether there were any.
+    pub fn expire(&mut self, now: Instant) -> bool {
+        let count = self.visible.len();
+        self.visible
+            .retain(|toast| toast.expires_at().is_none_or(|expires_at| expires_at > now));
+        self.visible.len() != count
+    }
+
+    /// Time until the next toast times out, `None` if none of the visible ones ever does.
+    pub fn next_timeout(&self, now: Instant) -> Option<Duration> {
+        self.visible
+            .iter()
+            .filter_map(Toast::expires_at)
+            .min()
+            .map(|expires_at| expires_at.saturating_duration_since(now))
+    }
+
+    pub fn visible(&self) -> impl Iterator<Item = &Toast> {
+        self.visible.iter()
+    }
+
+    /// Every toast shown so far, oldest first, including the dismissed and timed out ones.
+    pub fn history(&self) -> impl DoubleEndedIterator<Item = &Toast> {
+        self.history.iter()
+    }
+
+    pub fn history_len(&self) -> usize {
+        self.history.len()
+    }
+
+    pub fn clear_history(&mut self) {
+        self.history.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toasts_expire_by_severity() {
+        let mut notifications = Notifications::default();
+        notifications.push(Severity::Info, "saved");
+        notifications.push(Severity::Warning, "suspicious");
+        notifications.push(Severity::Error, "failed");
+
+        let later = Instant::now() + Duration::from_secs(INFO_TIMEOUT_SECS + 1);
+        assert!(notifications.expire(later));
+        let texts: Vec<_> = notifications.visible().map(|toast| &toast.text).collect();
+        assert_eq!(texts, ["suspicious", "failed"]);
+
+        let much_later = later + Duration::from_secs(WARNING_TIMEOUT_SECS);
+        assert!(notifications.expire(much_later));
+        assert_eq!(notifications.next_timeout(much_later), None);
+        assert_eq!(notifications.visible().count(), 1);
+        assert_eq!(notifications.history_len(), 3);
+    }
+
+    #[test]
+    fn stacked_toasts_make_room_for_new_ones() {
+        let mut notifications = Notifications::default();
+        let ids: Vec<_> = (0..MAX_VISIBLE_TOASTS + 2)
+            .map(|n| notifications.push(Severity::Info, n.to_string()))
+            .collect();
+        let visible: Vec<_> = notifications.visible().map(|toast| toast.id).collect();
+        assert_eq!(visible, ids[2..]);
+
+        notifications.dismiss(ids[3]);
+        assert_eq!(notifications.visible().count(), MAX_VISIBLE_TOASTS - 1);
+        assert_eq!(notifications.history_len(), ids.len());
+    }
+
+    #[test]
+    fn unique_toasts_are_shown_once() {
+        let mut notifications = Notifications::default();
+        let id = notifications.push_unique(Severity::Error, "can't save");
+        assert_eq!(notifications.push_unique(Severity::Error, "can't save"), id);
+        notifications.push_unique(Severity::Warning, "can't save");
+        assert_eq!(notifications.visible().count(), 2);
+        assert_eq!(notifications.history_len(), 2);
+
+        // Once dismissed, the same failure is shown again
+        notifications.dismiss(id);
+        assert_ne!(notifications.push_unique(Severity::Error, "can't save"), id);
+    }
+
+    #[test]
+    #[cfg(feature = "retained-mode")]
+    fn replaced_toasts_keep_their_place() {
+        let mut notifications = Notifications::default();
+        let id = notifications.replace(None, Severity::Warning, "channel 1 suspicious");
+        notifications.push(Severity::Info, "saved");
+        assert_eq!(
+            notifications.replace(Some(id), Severity::Success, "channel 1 normal"),
+            id
+        );
+
+        let texts: Vec<_> = notifications
+            .visible()
+            .map(|toast| toast.text.as_str())
+            .collect();
+        assert_eq!(texts, ["channel 1 normal", "saved"]);
+        let texts: Vec<_> = notifications
+            .history()
+            .map(|toast| toast.text.as_str())
+            .collect();
+        assert_eq!(texts, ["channel 1 normal", "saved"]);
+
+        // Once it's gone, the next change gets a new toast
+        notifications.dismiss(id);
+        assert_ne!(
+            notifications.replace(Some(id), Severity::Warning, "channel 1 suspicious"),
+            id
+        );
+    }
+}
@@ -3,95 +3,1538 @@
 // Useful links:
 // https://iced.rs/
 
+use crate::action_bar::{ActionButton, BarAction};
+use crate::app_config;
+use crate::app_theme::{self, Palette, PaletteColor, Rgb, ThemeChoice};
+use crate::channel_data_source::{self, ChannelDataSource};
+use crate::channel_history::{self, SPARKLINE_WIDTH};
+use crate::channel_metadata::MetadataField;
+use crate::channel_model::{
+    self, ChannelDataRow, ChannelInfo, ChannelMessage, ChannelModel, BACKUP_CHANNEL_INDEX,
+    DEFAULT_CHANNELS_COUNT, MAX_CHANNELS_COUNT, MIN_CHANNELS_COUNT,
+};
+use crate::channel_overview::{ChannelOverview, OverviewColumn};
+use crate::channel_statistics::ChannelStatistics;
+use crate::channel_value::{self, ChannelValue, ValueKind};
+use crate::common::format_time_of_day;
 use crate::common::ApplicationTab;
+use crate::comparison_table::{self, Cell, TableColumn, TableRow};
+use crate::event_log::Transition;
+use crate::export::{self, ExportFormat, Snapshot};
+use crate::import;
+use crate::key_bindings::{KeyBindings, KeyCombo, ShortcutAction};
+use crate::message_recorder::{self, InitialState, MessageRecorder, Recording, Replay, ReplayMode};
+use crate::navigation::NavigationHistory;
+use crate::notifications::{Notifications, Severity};
+use crate::session::{self, SessionState, SESSION_SCHEMA_VERSION};
+use crate::startup_args::parsed_startup_option;
+use crate::suspicious_rules::{self, RuleSet, SuspiciousRule};
+use crate::toast_overlay;
+
+use std::collections::HashMap;
+use std::future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
 
 use iced::{
-    widget::{button, slider, text, text_input, Button, Column, Container, Row, Rule, Space},
-    Element, Length, Sandbox, Settings,
+    event, executor, keyboard, subscription, time,
+    widget::{
+        button, checkbox, pick_list, scrollable, slider, text, text_input, tooltip, Button, Column,
+        Container, Row, Rule, Space,
+    },
+    window, Application, Color, Command, Element, Event, Length, Settings, Subscription, Theme,
 };
+use serde::{Deserialize, Serialize};
 
-use rand::{thread_rng, Rng};
+pub fn run() -> Result<(), Box<dyn std::error::Error>> {
+    // Headless export and replay for scripts, same code paths as the Export buttons and the
+    // replay in the window. Without `--replay` the export holds the first sample of the data
+    // source, fresh random values unless `--source` or `--seed` pins them down.
+    let export_path = export::path_from_startup_args();
+    let is_headless_replay = message_recorder::replay_from_startup_args()
+        .is_some_and(|(_, mode)| mode == ReplayMode::Headless);
+    if export_path.is_some() || is_headless_replay {
+        let (mut app, _) = ChannelBasedApp::new(());
+        app.finish_replay();
+        match export_path {
+            Some(path) => {
+                for written in app.export_snapshot(&path, ExportFormat::from_path(&path))? {
+                    println!("Exported {}", written.display());
+                }
+            }
+            None => println!("{}", app.state_summary()),
+        }
+        return Ok(());
+    }
 
-pub fn run() -> iced::Result {
-    ChannelBasedApp::run(Settings::default())
+    // Exit is handled by the app itself so the session can be saved first
+    ChannelBasedApp::run(Settings {
+        exit_on_close_request: false,
+        ..Settings::default()
+    })?;
+    Ok(())
 }
 
 //*  Constants */
-pub const CHANNELS_COUNT: usize = 9;
-pub const INVALID_CHANNEL_INDEX: usize = usize::MAX;
-pub const BACKUP_CHANNEL_INDEX: usize = 0;
-
-pub const LOW_INTEGER_LIMIT: u32 = 1;
-pub const HIGH_INTEGER_LIMIT: u32 = 100;
-pub const SUSPICIOUS_LIMIT: u32 = 75;
-
-#[derive(Default)]
-pub struct ChannelInfo {
-    pub integer_value: u32,
-    pub is_suspicious: bool,
-}
+pub const CHANNELS_COUNT_FLAG: &str = "--channels";
+pub const CHANNELS_COUNT_ENV: &str = "CHANNELS_COUNT";
+
+pub const DEFAULT_DATA_FILE_NAME: &str = "channels_snapshot";
+
+pub const MIN_SAMPLE_INTERVAL_MS: u32 = 100;
+pub const MAX_SAMPLE_INTERVAL_MS: u32 = 5000;
+
+pub const REPLAY_TICK_MS: u64 = 10;
+/// How often visible toasts are checked for timeouts.
+pub const TOAST_TICK_MS: u64 = 250;
 
 trait ChannelInfoUIExt {
-    fn value_as_text(&self) -> String;
-    fn suspicious_as_text(&self) -> String;
+    fn value_as_text(&self, kind: &ValueKind) -> String;
+    fn label_color(&self) -> Option<Color>;
+    fn suspicious_as_text(&self, current_limit: f64) -> String;
+    fn history_as_text(&self, kind: &ValueKind) -> String;
+    fn delta_as_text(&self, previous: &ChannelInfo, kind: &ValueKind) -> String;
+    fn change_percent_as_text(&self, previous: &ChannelInfo) -> String;
+    fn status_change_as_text(&self, previous: &ChannelInfo) -> String;
+    fn status_change_color(&self, previous: &ChannelInfo, palette: &Palette) -> Option<Color>;
 }
 
 impl ChannelInfoUIExt for ChannelInfo {
-    fn value_as_text(&self) -> String {
-        self.integer_value.to_string()
+    fn value_as_text(&self, kind: &ValueKind) -> String {
+        let value = kind.format_value(&self.value);
+        let value = if self.metadata.unit.is_empty() {
+            value
+        } else {
+            format!("{value} {}", self.metadata.unit)
+        };
+        if self.is_overridden {
+            format!("{value} (manual)")
+        } else {
+            value
+        }
+    }
+    fn label_color(&self) -> Option<Color> {
+        self.metadata.rgb().map(rgb_color)
+    }
+    fn suspicious_as_text(&self, current_limit: f64) -> String {
+        match &self.suspicious_rule {
+            Some(rule) => format!("Yes ({})", rule.explain(current_limit)),
+            None => "No".to_string(),
+        }
     }
-    fn suspicious_as_text(&self) -> String {
-        (if self.is_suspicious { "Yes" } else { "No" }).to_string()
+    fn history_as_text(&self, kind: &ValueKind) -> String {
+        format!(
+            "{} {} ({}s)",
+            self.history.sparkline(SPARKLINE_WIDTH, kind.range()),
+            self.history.trend().as_symbol(),
+            self.history.span().as_secs()
+        )
+    }
+    fn delta_as_text(&self, previous: &ChannelInfo, kind: &ValueKind) -> String {
+        let delta = self.value.scalar() - previous.value.scalar();
+        let sign = if delta < 0.0 { "" } else { "+" };
+        format!("{sign}{}", kind.format_scalar(delta))
+    }
+    fn change_percent_as_text(&self, previous: &ChannelInfo) -> String {
+        let previous_value = previous.value.scalar();
+        if previous_value == 0.0 {
+            return "n/a".to_string();
+        }
+        let delta = self.value.scalar() - previous_value;
+        // Relative to the size of the previous value, so signed values keep the direction
+        format!("{:+.1}%", delta / previous_value.abs() * 100.0)
+    }
+    fn status_change_as_text(&self, previous: &ChannelInfo) -> String {
+        match (previous.is_suspicious(), self.is_suspicious()) {
+            (false, true) => "Became suspicious",
+            (true, false) => "Became normal",
+            _ => "Same",
+        }
+        .to_string()
+    }
+    // Cells take the status colour of the current channel when only one of the compared
+    // channels is suspicious, by whatever rule
+    fn status_change_color(&self, previous: &ChannelInfo, palette: &Palette) -> Option<Color> {
+        match (previous.is_suspicious(), self.is_suspicious()) {
+            (false, true) => Some(rgb_color(palette.suspicious)),
+            (true, false) => Some(rgb_color(palette.normal)),
+            _ => None,
+        }
     }
 }
 
+/// Initial number of channels from `--channels` or `CHANNELS_COUNT`.
+pub fn channels_count_from_startup_args() -> usize {
+    parsed_startup_option::<usize>(CHANNELS_COUNT_FLAG, CHANNELS_COUNT_ENV)
+        .unwrap_or(DEFAULT_CHANNELS_COUNT)
+        .clamp(MIN_CHANNELS_COUNT, MAX_CHANNELS_COUNT)
+}
+
+// A read that panicked leaves the source as it was, so a poisoned lock is still usable
+fn lock_source(
+    data_source: &Mutex<Box<dyn ChannelDataSource>>,
+) -> MutexGuard<'_, Box<dyn ChannelDataSource>> {
+    data_source.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+pub(crate) fn rgb_color([red, green, blue]: Rgb) -> Color {
+    Color::from_rgb8(red, green, blue)
+}
+
+fn iced_theme(choice: &ThemeChoice) -> Theme {
+    let palette = choice.palette();
+    Theme::custom(iced::theme::Palette {
+        background: rgb_color(palette.background),
+        text: rgb_color(palette.text),
+        primary: rgb_color(palette.primary),
+        success: rgb_color(palette.normal),
+        danger: rgb_color(palette.suspicious),
+    })
+}
+
+fn bar_message(action: &BarAction) -> Message {
+    match action {
+        BarAction::RegenerateData => Message::RegenerateData,
+        BarAction::JumpToFirstSuspicious => Message::JumpToFirstSuspicious,
+        BarAction::Export(format) => Message::ExportSnapshot(*format),
+        BarAction::ToggleStreaming => Message::ToggleStreaming,
+        BarAction::SelectChannel(channel) => Message::ButtonPressed(*channel),
+        BarAction::ChangeChannel(change) => Message::ChangeChannel(*change),
+        BarAction::ClearCurrent => Message::ClearChannelRow(ChannelDataRow::Current),
+        BarAction::ClearPrevious => Message::ClearChannelRow(ChannelDataRow::Previous),
+        BarAction::NavigateBack => Message::NavigateBack,
+        BarAction::NavigateForward => Message::NavigateForward,
+        BarAction::AcknowledgeAllEvents => Message::AcknowledgeAllEvents,
+        BarAction::Undo => Message::Undo,
+        BarAction::Redo => Message::Redo,
+    }
+}
+
+// Key names are the ones egui uses, so both front ends share the names in `key_bindings`.
+// Keys without a name there (media keys, lone modifiers, ...) can't be bound.
+fn key_combo(key_code: keyboard::KeyCode, modifiers: keyboard::Modifiers) -> Option<KeyCombo> {
+    use keyboard::KeyCode;
+
+    let name = match key_code {
+        KeyCode::Key0 | KeyCode::Numpad0 => "0",
+        KeyCode::Key1 | KeyCode::Numpad1 => "1",
+        KeyCode::Key2 | KeyCode::Numpad2 => "2",
+        KeyCode::Key3 | KeyCode::Numpad3 => "3",
+        KeyCode::Key4 | KeyCode::Numpad4 => "4",
+        KeyCode::Key5 | KeyCode::Numpad5 => "5",
+        KeyCode::Key6 | KeyCode::Numpad6 => "6",
+        KeyCode::Key7 | KeyCode::Numpad7 => "7",
+        KeyCode::Key8 | KeyCode::Numpad8 => "8",
+        KeyCode::Key9 | KeyCode::Numpad9 => "9",
+        KeyCode::A => "A",
+        KeyCode::B => "B",
+        KeyCode::C => "C",
+        KeyCode::D => "D",
+        KeyCode::E => "E",
+        KeyCode::F => "F",
+        KeyCode::G => "G",
+        KeyCode::H => "H",
+        KeyCode::I => "I",
+        KeyCode::J => "J",
+        KeyCode::K => "K",
+        KeyCode::L => "L",
+        KeyCode::M => "M",
+        KeyCode::N => "N",
+        KeyCode::O => "O",
+        KeyCode::P => "P",
+        KeyCode::Q => "Q",
+        KeyCode::R => "R",
+        KeyCode::S => "S",
+        KeyCode::T => "T",
+        KeyCode::U => "U",
+        KeyCode::V => "V",
+        KeyCode::W => "W",
+        KeyCode::X => "X",
+        KeyCode::Y => "Y",
+        KeyCode::Z => "Z",
+        KeyCode::F1 => "F1",
+        KeyCode::F2 => "F2",
+        KeyCode::F3 => "F3",
+        KeyCode::F4 => "F4",
+        KeyCode::F5 => "F5",
+        KeyCode::F6 => "F6",
+        KeyCode::F7 => "F7",
+        KeyCode::F8 => "F8",
+        KeyCode::F9 => "F9",
+        KeyCode::F10 => "F10",
+        KeyCode::F11 => "F11",
+        KeyCode::F12 => "F12",
+        KeyCode::F13 => "F13",
+        KeyCode::F14 => "F14",
+        KeyCode::F15 => "F15",
+        KeyCode::F16 => "F16",
+        KeyCode::F17 => "F17",
+        KeyCode::F18 => "F18",
+        KeyCode::F19 => "F19",
+        KeyCode::F20 => "F20",
+        KeyCode::F21 => "F21",
+        KeyCode::F22 => "F22",
+        KeyCode::F23 => "F23",
+        KeyCode::F24 => "F24",
+        KeyCode::Left => "Left",
+        KeyCode::Right => "Right",
+        KeyCode::Up => "Up",
+        KeyCode::Down => "Down",
+        KeyCode::Escape => "Escape",
+        KeyCode::Tab => "Tab",
+        KeyCode::Backspace => "Backspace",
+        KeyCode::Enter | KeyCode::NumpadEnter => "Enter",
+        KeyCode::Insert => "Insert",
+        KeyCode::Delete => "Delete",
+        KeyCode::Home => "Home",
+        KeyCode::End => "End",
+        KeyCode::PageUp => "PageUp",
+        KeyCode::PageDown => "PageDown",
+        KeyCode::Space => "Space",
+        KeyCode::Colon => "Colon",
+        KeyCode::Comma | KeyCode::NumpadComma => "Comma",
+        KeyCode::Minus | KeyCode::NumpadSubtract => "Minus",
+        KeyCode::Period | KeyCode::NumpadDecimal => "Period",
+        KeyCode::Plus | KeyCode::NumpadAdd => "Plus",
+        KeyCode::Equals | KeyCode::NumpadEquals => "Equals",
+        KeyCode::Semicolon => "Semicolon",
+        KeyCode::Backslash => "Backslash",
+        KeyCode::Slash | KeyCode::NumpadDivide => "Slash",
+        KeyCode::LBracket => "OpenBracket",
+        KeyCode::RBracket => "CloseBracket",
+        KeyCode::Grave => "Backtick",
+        KeyCode::Apostrophe => "Quote",
+        KeyCode::Copy => "Copy",
+        KeyCode::Cut => "Cut",
+        KeyCode::Paste => "Paste",
+        _ => return None,
+    };
+    Some(KeyCombo::new(
+        name,
+        modifiers.control(),
+        modifiers.shift(),
+        modifiers.alt(),
+    ))
+}
+
 // TODOs:
 // Play with stretching the window
 
 struct ChannelBasedApp {
-    previous_channel_index: usize,
-    current_channel_index: usize,
-    channel_data: [ChannelInfo; CHANNELS_COUNT],
-    current_suspicious_limit: u32,
+    model: ChannelModel,
+    key_bindings: KeyBindings,
+    action_bar: Vec<ActionButton>,
+    is_event_log_visible: bool,
+    data_file_path: String,
+    session_path: Option<PathBuf>,
+    file_status: Option<String>,
+    file_errors: Vec<String>,
+    /// Shared with the sample reads running on the executor.
+    data_source: Arc<Mutex<Box<dyn ChannelDataSource>>>,
+    /// `describe` and `seed` of the data source, so drawing never waits for a slow read.
+    data_source_description: String,
+    seed: Option<u64>,
+    /// Set while a sample is read, sample ticks in the meantime don't queue up more reads.
+    is_sample_pending: bool,
+    /// Kind of values the data source reports, decides ranges and formatting.
+    value_kind: ValueKind,
+    sample_interval: Duration,
+    is_streaming_paused: bool,
+    overview: ChannelOverview,
+    is_overview_visible: bool,
+    /// Channels compared with the previous one below the current channel, in pinning order.
+    pinned_channels: Vec<usize>,
+    /// Whether the table's value cells take manual values.
+    is_edit_mode: bool,
+    /// Text typed into value cells and not applied yet, by channel index.
+    value_edits: HashMap<usize, String>,
+    theme_choice: ThemeChoice,
+    /// Palette offered as the custom theme, kept while other themes are selected.
+    custom_palette: Palette,
+    /// Text of the custom palette inputs in `PaletteColor::ALL` order.
+    palette_inputs: Vec<String>,
+    /// Set by valid palette edits until they're saved with Enter or on close.
+    is_palette_unsaved: bool,
+    theme_path: Option<PathBuf>,
+    /// Set with `--record`, every message handled by `update` is written to it.
+    recorder: Option<MessageRecorder>,
+    /// Set with `--replay`, kept after it finished so the About tab can say so.
+    replay: Option<Replay<Message>>,
+    notifications: Notifications,
+    is_notification_history_visible: bool,
+    /// Newest event of the event log that got a toast, older ones are never announced.
+    last_notified_event: Option<u64>,
+    /// Toast announcing the latest suspicious state changes, replaced by the next ones.
+    transition_toast: Option<u64>,
 
     active_tab: ApplicationTab,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-enum ChannelDataRow {
-    Previous,
-    Current,
-}
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 enum Message {
     IgnoreInput, // used at least for TextInput's to be 'read-only', but still can copy the values
     TabSelected(ApplicationTab),
     ButtonPressed(usize),
     ChangeChannel(i32),
+    ModifyingChannelsCount(u32),
+    ReleasedChannelsCountSlider,
     ClearChannelRow(ChannelDataRow),
-    ModifyingSuspiciosValue(u32),
+    NavigateBack,
+    NavigateForward,
+    JumpToNavigationEntry(usize),
+    ModifyingSuspiciosValue(f64),
     ReleasedSuspiciousSlider,
+    SampleTick,
+    SampleReceived(Vec<ChannelValue>),
+    SampleFailed(String),
+    ModifyingSampleInterval(u32),
+    ToggleStreaming,
+    ToggleEventLog,
+    AcknowledgeEvent(u64),
+    AcknowledgeAllEvents,
+    ClearAcknowledgedEvents,
+    DataFilePathChanged(String),
+    ExportSnapshot(ExportFormat),
+    ImportSnapshot(ExportFormat),
+    ValuesImported(Vec<(usize, ChannelValue)>),
+    CloseRequested,
+    KeyPressed(KeyCombo),
+    RegenerateData,
+    JumpToFirstSuspicious,
+    RunActionButton(usize),
+    ToggleOverview,
+    SortOverview(OverviewColumn),
+    OverviewSuspiciousOnly(bool),
+    OverviewMinValueChanged(String),
+    OverviewMaxValueChanged(String),
+    EditChannelMetadata(usize, MetadataField, String),
+    ThemeSelected(ThemeChoice),
+    Undo,
+    Redo,
+    PaletteColorChanged(PaletteColor, String),
+    PaletteColorSubmitted,
+    PinChannel(usize),
+    UnpinChannel(usize),
+    ToggleEditMode,
+    ValueEdited(usize, String),
+    ApplyValueEdit(usize),
+    ClearOverride(usize),
+    ReplayTick,
+    ExpireToasts,
+    DismissToast(u64),
+    ToggleNotificationHistory,
+    ClearNotificationHistory,
+}
+
+impl Message {
+    // Messages reaching outside the app, skipped when replayed: files may have changed
+    // since the recording and the window must stay open
+    fn has_side_effects(&self) -> bool {
+        matches!(
+            self,
+            Message::ExportSnapshot(_) | Message::ImportSnapshot(_) | Message::CloseRequested
+        )
+    }
+
+    // Ticks and toast timeouts depend on the wall clock and do nothing in a replay, the
+    // samples they pull are recorded as they arrive
+    fn is_recorded(&self) -> bool {
+        !matches!(
+            self,
+            Message::SampleTick | Message::ReplayTick | Message::ExpireToasts
+        )
+    }
+}
+
+/// Everything the app reads from the command line, environment and files at startup.
+struct StartupOptions {
+    channels_count: usize,
+    history_capacity: usize,
+    value_kind: ValueKind,
+    rules: RuleSet,
+    key_bindings: KeyBindings,
+    action_bar: Vec<ActionButton>,
+    data_source: Box<dyn ChannelDataSource>,
+    sample_interval: Duration,
+    /// Where the session is loaded from and saved to, `None` to start from the defaults.
+    session_path: Option<PathBuf>,
+    theme_choice: ThemeChoice,
+    theme_path: Option<PathBuf>,
+    replay: Option<(PathBuf, ReplayMode)>,
+    record_path: Option<PathBuf>,
+}
+
+impl StartupOptions {
+    fn from_startup_args() -> Self {
+        let value_kind = channel_value::kind_from_startup_args();
+        StartupOptions {
+            channels_count: channels_count_from_startup_args(),
+            history_capacity: channel_history::capacity_from_startup_args(),
+            rules: suspicious_rules::from_startup_args(),
+            key_bindings: KeyBindings::from_startup_args(KeyBindings::retained_defaults()),
+            action_bar: app_config::from_startup_args().action_bar(),
+            data_source: channel_data_source::from_startup_args(&value_kind),
+            value_kind,
+            sample_interval: channel_data_source::sample_interval_from_startup_args(),
+            session_path: session::session_file_path(),
+            theme_choice: app_theme::from_startup_args(),
+            theme_path: app_theme::theme_file_path(),
+            replay: message_recorder::replay_from_startup_args(),
+            record_path: message_recorder::record_path_from_startup_args(),
+        }
+    }
 }
 
 impl ChannelBasedApp {
-    fn init_data(&mut self) {
-        // Initialization of channel infos
-        for i in 0..CHANNELS_COUNT {
-            let mut rng = thread_rng();
-            let generated_int = rng.gen_range(LOW_INTEGER_LIMIT..=HIGH_INTEGER_LIMIT) as u32;
-            let is_suspicious = generated_int > self.current_suspicious_limit;
-            let channel_info = ChannelInfo {
-                integer_value: generated_int,
-                is_suspicious,
+    // Everything outside the app comes in through `options`, so tests can start it without
+    // touching the user's session, theme or command line
+    fn with_options(options: StartupOptions) -> (Self, Command<Message>) {
+        let StartupOptions {
+            channels_count,
+            history_capacity,
+            value_kind,
+            rules,
+            key_bindings,
+            action_bar,
+            data_source,
+            sample_interval,
+            session_path,
+            theme_choice,
+            theme_path,
+            replay,
+            record_path,
+        } = options;
+        let data_source_description = data_source.describe();
+        let seed = data_source.seed();
+        let mut app = ChannelBasedApp {
+            model: ChannelModel::new(
+                channels_count,
+                history_capacity,
+                &value_kind,
+                value_kind.default_limit(),
+                rules,
+            ),
+            active_tab: ApplicationTab::Home,
+            key_bindings,
+            action_bar,
+            is_event_log_visible: true,
+            data_file_path: DEFAULT_DATA_FILE_NAME.to_string(),
+            session_path,
+            file_status: None,
+            file_errors: Vec::new(),
+            data_source: Arc::new(Mutex::new(data_source)),
+            data_source_description,
+            seed,
+            is_sample_pending: false,
+            value_kind,
+            sample_interval,
+            is_streaming_paused: false,
+            overview: ChannelOverview::default(),
+            is_overview_visible: false,
+            pinned_channels: Vec::new(),
+            is_edit_mode: false,
+            value_edits: HashMap::new(),
+            theme_choice,
+            custom_palette: theme_choice.palette(),
+            palette_inputs: PaletteColor::ALL
+                .iter()
+                .map(|&which| app_theme::to_hex(theme_choice.palette().color(which)))
+                .collect(),
+            is_palette_unsaved: false,
+            theme_path,
+            recorder: None,
+            replay: None,
+            notifications: Notifications::default(),
+            is_notification_history_visible: false,
+            last_notified_event: None,
+            transition_toast: None,
+        };
+
+        let session = app.session_path.as_deref().and_then(SessionState::load);
+        if let Some(session) = &session {
+            let range = app.value_kind.range();
+            app.model.current_suspicious_limit = app
+                .value_kind
+                .round_to_step(session.suspicious_limit.clamp(*range.start(), *range.end()));
+        }
+
+        // TODO it might be separated button, Initialize
+        if let Some(values) = app.read_sample() {
+            channel_model::update(&mut app.model, ChannelMessage::SampleReceived(values));
+        }
+
+        let command = match session {
+            Some(session) => {
+                app.restore_session(session);
+                Command::none()
+            }
+            None => app.handle_message(Message::ButtonPressed(BACKUP_CHANNEL_INDEX + 1)),
+        };
+
+        if let Some((path, mode)) = replay {
+            match Recording::load(&path) {
+                Ok(recording) => {
+                    app.apply_initial_state(recording.initial_state);
+                    app.replay = Some(Replay::new(recording.messages, mode));
+                    // A replay must not overwrite the user's session or theme
+                    app.session_path = None;
+                    app.theme_path = None;
+                }
+                Err(err) => eprintln!("Can't replay {}: {err}", path.display()),
+            }
+        } else if let Some(path) = record_path {
+            match MessageRecorder::create(&path, &app.initial_state()) {
+                Ok(recorder) => app.recorder = Some(recorder),
+                Err(err) => eprintln!("Can't record to {}: {err}", path.display()),
+            }
+        }
+        // Channels that start out suspicious are in the event log already, no toast for them
+        app.last_notified_event = app
+            .model
+            .event_log
+            .events()
+            .next_back()
+            .map(|event| event.id);
+
+        (app, command)
+    }
+
+    // Reads one sample for every channel from the data source, blocking until it arrives
+    fn read_sample(&mut self) -> Option<Vec<ChannelValue>> {
+        let result = lock_source(&self.data_source).next_values(self.model.channel_data.len());
+        result
+            .map_err(|err| {
+                eprintln!(
+                    "Failed to read from {}: {err}",
+                    self.data_source_description
+                )
+            })
+            .ok()
+    }
+
+    // Samples are read on a blocking thread so a slow file or socket doesn't freeze the window.
+    // They come back as a message so recordings hold the values instead of the ticks.
+    // A replay brings its own samples, and a slow source gets no new request while one is
+    // pending.
+    fn pull_sample(&mut self) -> Command<Message> {
+        if self.is_replaying() || self.is_sample_pending {
+            return Command::none();
+        }
+        self.is_sample_pending = true;
+        let data_source = Arc::clone(&self.data_source);
+        let channels_count = self.model.channel_data.len();
+        let description = self.data_source_description.clone();
+        Command::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    lock_source(&data_source).next_values(channels_count)
+                })
+                .await
+                .map_err(io::Error::other)
+                .and_then(|result| result)
+                .map_err(|err| format!("Failed to read from {description}: {err}"))
+            },
+            |result| match result {
+                Ok(values) => Message::SampleReceived(values),
+                Err(err) => Message::SampleFailed(err),
+            },
+        )
+    }
+
+    fn is_replaying(&self) -> bool {
+        self.replay
+            .as_ref()
+            .is_some_and(|replay| !replay.is_finished())
+    }
+
+    fn initial_state(&self) -> InitialState {
+        InitialState {
+            values: self
+                .model
+                .channel_data
+                .iter()
+                .map(|channel_info| channel_info.value.clone())
+                .collect(),
+            suspicious_limit: self.model.current_suspicious_limit,
+            value_kind: self.value_kind.clone(),
+            previous_channel: self.model.navigation.previous().map(|index| index + 1),
+            current_channel: self.model.navigation.current().map(|index| index + 1),
+            channel_metadata: self
+                .model
+                .channel_data
+                .iter()
+                .map(|channel_info| channel_info.metadata.clone())
+                .collect(),
+            seed: self.seed,
+        }
+    }
+
+    // Starts from the recorded state instead of the session and data source
+    fn apply_initial_state(&mut self, state: InitialState) {
+        self.value_kind = state.value_kind;
+        self.model = ChannelModel::new(
+            state.values.len(),
+            self.model.history_capacity,
+            &self.value_kind,
+            state.suspicious_limit,
+            self.model.rules.clone(),
+        );
+        channel_model::update(
+            &mut self.model,
+            ChannelMessage::SampleReceived(state.values),
+        );
+        self.restore_selection(state.previous_channel, state.current_channel);
+        let channels_count = self.model.channels_count();
+        self.pinned_channels.retain(|&index| index < channels_count);
+        for (channel_info, metadata) in self
+            .model
+            .channel_data
+            .iter_mut()
+            .zip(state.channel_metadata)
+        {
+            channel_info.metadata = metadata;
+        }
+    }
+
+    // Hands the messages due by now to `handle_message`, as if they came from the window
+    fn replay_due_messages(&mut self) -> Command<Message> {
+        let was_replaying = self.is_replaying();
+        let Some(replay) = &mut self.replay else {
+            return Command::none();
+        };
+        let messages = replay.due_messages();
+        let command = self.replay_messages(messages);
+        if was_replaying && !self.is_replaying() {
+            self.notifications
+                .push(Severity::Info, "Replay finished, live samples stay off");
+        }
+        command
+    }
+
+    // Headless replays and exports don't wait for ticks, whatever the replay mode
+    fn finish_replay(&mut self) {
+        let Some(replay) = &mut self.replay else {
+            return;
+        };
+        let messages = replay.drain_all();
+        let _ = self.replay_messages(messages);
+    }
+
+    // Recordings made before keys and buttons were resolved may still hold them
+    fn replay_messages(&mut self, messages: Vec<Message>) -> Command<Message> {
+        let mut commands = Vec::new();
+        for message in messages {
+            for message in self.resolve(message) {
+                if !message.has_side_effects() {
+                    commands.push(self.handle_message(message));
+                }
+            }
+        }
+        Command::batch(commands)
+    }
+
+    // Key presses and action buttons stand for other messages, which are the ones recorded so
+    // a replay doesn't depend on the key bindings and action bar of whoever replays it
+    fn resolve(&self, message: Message) -> Vec<Message> {
+        match message {
+            Message::KeyPressed(combo) => {
+                let Some(action) = self.key_bindings.action_for(&combo) else {
+                    return Vec::new();
+                };
+                let message = match action {
+                    ShortcutAction::SelectChannel(channel) => Message::ButtonPressed(*channel),
+                    ShortcutAction::ChangeChannel(change) => Message::ChangeChannel(*change),
+                    ShortcutAction::NextTab => Message::TabSelected(self.active_tab.next()),
+                    ShortcutAction::ClearCurrentRow => {
+                        Message::ClearChannelRow(ChannelDataRow::Current)
+                    }
+                    ShortcutAction::Undo => Message::Undo,
+                    ShortcutAction::Redo => Message::Redo,
+                    _ => return Vec::new(),
+                };
+                vec![message]
+            }
+            Message::RunActionButton(button_index) => self
+                .action_bar
+                .get(button_index)
+                .map(|action_button| action_button.actions.iter().map(bar_message).collect())
+                .unwrap_or_default(),
+            message => vec![message],
+        }
+    }
+
+    fn replay_status(&self) -> Option<String> {
+        let replay = self.replay.as_ref()?;
+        Some(if replay.is_finished() {
+            "Replay finished, live samples stay off".to_string()
+        } else {
+            format!(
+                "Replaying ({:?}), {} message(s) left",
+                replay.mode(),
+                replay.remaining()
+            )
+        })
+    }
+
+    // One toast per suspicious/normal transition logged since the last call
+    // Changes from one update share a toast, which replaces the one of the previous changes
+    // while it's still up, so a noisy source doesn't bury the other toasts
+    fn notify_suspicious_changes(&mut self) {
+        let last_notified_event = self.last_notified_event;
+        let mut became_suspicious = Vec::new();
+        let mut became_normal = Vec::new();
+        for event in self
+            .model
+            .event_log
+            .events()
+            .filter(|event| last_notified_event.is_none_or(|id| event.id > id))
+        {
+            let label = self
+                .model
+                .channel_data
+                .get(event.channel_index)
+                .map_or((event.channel_index + 1).to_string(), |channel_info| {
+                    channel_info.metadata.label(event.channel_index)
+                });
+            match &event.transition {
+                Transition::BecameSuspicious(reason) => became_suspicious.push((label, reason)),
+                Transition::BecameNormal => became_normal.push(label),
+            }
+            self.last_notified_event = Some(event.id);
+        }
+
+        let (severity, text) = match (became_suspicious.as_slice(), became_normal.as_slice()) {
+            ([], []) => return,
+            ([(label, reason)], []) => (
+                Severity::Warning,
+                format!("Channel {label} became suspicious ({reason})"),
+            ),
+            ([], [label]) => (
+                Severity::Success,
+                format!("Channel {label} is back to normal"),
+            ),
+            _ => {
+                let channels = |labels: Vec<&String>| match labels.as_slice() {
+                    [label] => format!("Channel {label}"),
+                    _ => format!(
+                        "Channels {}",
+                        labels
+                            .iter()
+                            .map(|label| label.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                };
+                let mut parts = Vec::new();
+                if !became_suspicious.is_empty() {
+                    let labels = became_suspicious.iter().map(|(label, _)| label).collect();
+                    parts.push(format!("{} became suspicious", channels(labels)));
+                }
+                if !became_normal.is_empty() {
+                    let labels = became_normal.iter().collect();
+                    parts.push(format!("{} back to normal", channels(labels)));
+                }
+                let severity = if became_suspicious.is_empty() {
+                    Severity::Success
+                } else {
+                    Severity::Warning
+                };
+                (severity, parts.join("; "))
+            }
+        };
+        self.transition_toast = Some(self.notifications.replace(
+            self.transition_toast,
+            severity,
+            text,
+        ));
+    }
+
+    // Printed by headless replays without `--export`
+    fn state_summary(&self) -> String {
+        let values: Vec<_> = self
+            .model
+            .channel_data
+            .iter()
+            .map(|channel_info| channel_info.value.to_string())
+            .collect();
+        let channel_text = |channel: Option<usize>| {
+            channel.map_or("-".to_string(), |index| (index + 1).to_string())
+        };
+        format!(
+            "Values: {}\nSuspicious limit: {}\nPrevious channel: {}\nCurrent channel: {}",
+            values.join(","),
+            self.model.current_suspicious_limit,
+            channel_text(self.model.navigation.previous()),
+            channel_text(self.model.navigation.current())
+        )
+    }
+
+    pub fn export_snapshot(&self, path: &Path, format: ExportFormat) -> io::Result<Vec<PathBuf>> {
+        Snapshot::new(
+            &self.model.channel_data,
+            &self.model.rules,
+            self.model.current_suspicious_limit,
+            &self.model.event_log,
+        )
+        .write(path, format)
+    }
+
+    // Replaces channel values with imported ones, all-or-nothing so a bad file can't leave
+    // a half-imported state behind. The values come back as a message so recordings hold them.
+    fn import_snapshot(&mut self, path: &Path, format: ExportFormat) -> Command<Message> {
+        self.file_errors.clear();
+        let report = match import::import_channels(
+            path,
+            format,
+            self.model.channel_data.len(),
+            &self.value_kind,
+            self.value_kind.range(),
+        ) {
+            Ok(report) => report,
+            Err(err) => {
+                let status = format!("Import from {} failed: {err}", path.display());
+                self.notifications.push(Severity::Error, status.clone());
+                self.file_status = Some(status);
+                return Command::none();
+            }
+        };
+
+        if !report.is_valid() {
+            let status = format!(
+                "Import from {} rejected, {} invalid row(s):",
+                path.display(),
+                report.errors.len()
+            );
+            self.notifications.push(Severity::Error, status.clone());
+            self.file_status = Some(status);
+            self.file_errors = report.errors.iter().map(|err| err.to_string()).collect();
+            return Command::none();
+        }
+
+        let kept = report
+            .values
+            .iter()
+            .filter(|&&(index, _)| self.model.is_overridden(index))
+            .count();
+        let mut status = format!(
+            "Imported {} channel(s) from {}, streaming paused",
+            report.values.len() - kept,
+            path.display()
+        );
+        if kept > 0 {
+            status.push_str(&format!(", {kept} overridden channel(s) kept"));
+        }
+        self.notifications.push(Severity::Success, status.clone());
+        self.file_status = Some(status);
+        Command::perform(future::ready(report.values), Message::ValuesImported)
+    }
+
+    fn session_state(&self) -> SessionState {
+        SessionState {
+            schema_version: SESSION_SCHEMA_VERSION,
+            suspicious_limit: self.model.current_suspicious_limit,
+            previous_channel: self.model.navigation.previous().map(|index| index + 1),
+            current_channel: self.model.navigation.current().map(|index| index + 1),
+            active_tab: self.active_tab.clone(),
+            pinned_channels: self.pinned_channels.iter().map(|index| index + 1).collect(),
+            channel_metadata: self
+                .model
+                .channel_data
+                .iter()
+                .map(|channel_info| channel_info.metadata.clone())
+                .collect(),
+        }
+    }
+
+    fn restore_session(&mut self, session: SessionState) {
+        self.restore_selection(session.previous_channel, session.current_channel);
+        self.active_tab = session.active_tab;
+        let channels_count = self.model.channels_count();
+        for channel in session.pinned_channels {
+            if (1..=channels_count).contains(&channel)
+                && !self.pinned_channels.contains(&(channel - 1))
+            {
+                self.pinned_channels.push(channel - 1);
+            }
+        }
+
+        for (channel_info, metadata) in self
+            .model
+            .channel_data
+            .iter_mut()
+            .zip(session.channel_metadata)
+        {
+            channel_info.metadata = metadata;
+        }
+    }
+
+    // Channels that don't exist with the current channel count are left unselected
+    fn restore_selection(
+        &mut self,
+        previous_channel: Option<usize>,
+        current_channel: Option<usize>,
+    ) {
+        let channels_count = self.model.channel_data.len();
+        let index = |channel: Option<usize>| {
+            channel
+                .filter(|channel| (1..=channels_count).contains(channel))
+                .map(|channel| channel - 1)
+        };
+        self.model.navigation =
+            NavigationHistory::with_selection(index(previous_channel), index(current_channel));
+    }
+
+    fn save_theme(&mut self) {
+        if let Some(path) = &self.theme_path {
+            if let Err(err) = self.theme_choice.save(path) {
+                self.notifications.push_unique(
+                    Severity::Error,
+                    format!("Can't save theme to {}: {err}", path.display()),
+                );
+            }
+        }
+    }
+
+    // Palette edits are saved on Enter rather than on every keystroke, or on close at the latest
+    fn save_palette(&mut self) {
+        if self.is_palette_unsaved {
+            self.is_palette_unsaved = false;
+            self.save_theme();
+        }
+    }
+
+    fn status_color(&self, channel_info: &ChannelInfo) -> Color {
+        rgb_color(
+            self.theme_choice
+                .palette()
+                .status_color(channel_info.is_suspicious()),
+        )
+    }
+
+    // Cell comparing a row with the first row of the table, empty in the first row itself
+    fn comparison_cell(
+        &self,
+        index: usize,
+        baseline: Option<usize>,
+        describe: impl Fn(&ChannelInfo, &ChannelInfo) -> String,
+    ) -> Cell {
+        let Some(baseline) = baseline else {
+            return Cell::default();
+        };
+        let (current, previous) = (
+            &self.model.channel_data[index],
+            &self.model.channel_data[baseline],
+        );
+        Cell::new(describe(current, previous))
+            .with_color(current.status_change_color(previous, &self.theme_choice.palette()))
+    }
+
+    // Previous and current channel first, then the pinned ones, each compared with the
+    // previous channel
+    fn comparison_table(&self) -> Element<'_, Message> {
+        let channel_data = &self.model.channel_data;
+        let limit = self.model.current_suspicious_limit;
+        let kind = &self.value_kind;
+        let value_column = if self.is_edit_mode {
+            TableColumn::new("Value", 2, |index, _| {
+                let channel_info = &channel_data[index];
+                let Some(text) = self.value_edits.get(&index) else {
+                    return Cell::new(channel_info.value.to_string());
+                };
+                let error = kind.parse_in_range(text, kind.range()).err();
+                Cell::new(text.clone()).with_error(error.map(|error| error.to_string()))
+            })
+            .editable(Message::ValueEdited, Message::ApplyValueEdit)
+        } else {
+            TableColumn::new("Value", 2, |index, _| {
+                let channel_info = &channel_data[index];
+                Cell::new(channel_info.value_as_text(kind))
+                    .with_color(Some(self.status_color(channel_info)))
+            })
+            .copyable()
+        };
+        let columns = [
+            value_column,
+            TableColumn::new("Suspicious", 2, |index, _| {
+                let channel_info = &channel_data[index];
+                Cell::new(channel_info.suspicious_as_text(limit))
+                    .with_color(Some(self.status_color(channel_info)))
+            })
+            .copyable(),
+            TableColumn::new("Channel", 2, |index, _| {
+                Cell::new(channel_data[index].metadata.label(index))
+            })
+            .copyable(),
+            TableColumn::new("History", 3, |index, _| {
+                Cell::new(channel_data[index].history_as_text(kind))
+            }),
+            TableColumn::new("Delta", 2, |index, baseline| {
+                self.comparison_cell(index, baseline, |current, previous| {
+                    current.delta_as_text(previous, kind)
+                })
+            }),
+            TableColumn::new("Change", 2, |index, baseline| {
+                self.comparison_cell(index, baseline, ChannelInfo::change_percent_as_text)
+            }),
+            TableColumn::new("Status", 2, |index, baseline| {
+                self.comparison_cell(index, baseline, ChannelInfo::status_change_as_text)
+            }),
+        ];
+
+        let pin_action = |channel_index: Option<usize>| {
+            channel_index
+                .filter(|index| !self.pinned_channels.contains(index))
+                .map(|index| ("Pin", Message::PinChannel(index)))
+        };
+        let override_action = |channel_index: Option<usize>| {
+            channel_index
+                .filter(|&index| channel_data[index].is_overridden)
+                .map(|index| ("Clear override", Message::ClearOverride(index)))
+        };
+        let mut rows = vec![
+            TableRow {
+                label: "Previous".to_string(),
+                channel_index: self.model.navigation.previous(),
+                actions: [("Clear", Message::ClearChannelRow(ChannelDataRow::Previous))]
+                    .into_iter()
+                    .chain(pin_action(self.model.navigation.previous()))
+                    .chain(override_action(self.model.navigation.previous()))
+                    .collect(),
+            },
+            TableRow {
+                label: "Current".to_string(),
+                channel_index: self.model.navigation.current(),
+                actions: [("Clear", Message::ClearChannelRow(ChannelDataRow::Current))]
+                    .into_iter()
+                    .chain(pin_action(self.model.navigation.current()))
+                    .chain(override_action(self.model.navigation.current()))
+                    .collect(),
+            },
+        ];
+        rows.extend(self.pinned_channels.iter().map(|&index| {
+            TableRow {
+                label: "Pinned".to_string(),
+                channel_index: Some(index),
+                actions: [("Unpin", Message::UnpinChannel(index))]
+                    .into_iter()
+                    .chain(override_action(Some(index)))
+                    .collect(),
+            }
+        }));
+
+        let table = comparison_table::view(
+            rows,
+            &columns,
+            Message::IgnoreInput,
+            rgb_color(self.theme_choice.palette().suspicious),
+        );
+        if !self.is_edit_mode {
+            return table;
+        }
+        let value_hint = match kind {
+            ValueKind::Fields(names) => format!("{} separated by ';', the first", names.join(", ")),
+            _ => "a value".to_string(),
+        };
+        let range = kind.range();
+        Column::new()
+            .spacing(10)
+            .push(text(format!(
+                "Type {value_hint} from {} to {} and press Enter to override a channel, samples \
+                 leave it alone until the override is cleared.",
+                kind.format_scalar(*range.start()),
+                kind.format_scalar(*range.end())
+            )))
+            .push(table)
+            .into()
+    }
+
+    fn theme_section(&self) -> Column<'_, Message> {
+        let mut section = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Alignment::Center)
+                .push(text("Theme:"))
+                .push(pick_list(
+                    ThemeChoice::all(self.custom_palette).to_vec(),
+                    Some(self.theme_choice),
+                    Message::ThemeSelected,
+                )),
+        );
+
+        if let ThemeChoice::Custom(palette) = self.theme_choice {
+            let mut colors = Row::new().spacing(10).align_items(iced::Alignment::Center);
+            for (which, input) in PaletteColor::ALL.into_iter().zip(&self.palette_inputs) {
+                colors = colors
+                    .push(text(format!("{}:", which.name())))
+                    .push(
+                        text_input("#rrggbb", input)
+                            .on_input(move |text| Message::PaletteColorChanged(which, text))
+                            .on_submit(Message::PaletteColorSubmitted)
+                            .width(90),
+                    )
+                    .push(text("■").style(rgb_color(palette.color(which))));
+            }
+            section = section.push(colors);
+        }
+        section
+    }
+
+    // Number button of a channel, in its colour and with its details as a tooltip
+    fn channel_button(&self, index: usize) -> Element<'_, Message> {
+        let channel_info = &self.model.channel_data[index];
+        let mut label = text(channel_info.metadata.label(index));
+        if let Some(color) = channel_info.label_color() {
+            label = label.style(color);
+        }
+        let status_style = if channel_info.is_suspicious() {
+            iced::theme::Button::Destructive
+        } else {
+            iced::theme::Button::Positive
+        };
+        let button = button(label)
+            .on_press(Message::ButtonPressed(index + 1))
+            .style(status_style)
+            .padding(if self.model.navigation.current() == Some(index) {
+                20
+            } else {
+                10
+            });
+
+        tooltip(
+            button,
+            channel_info.metadata.details(index),
+            tooltip::Position::Bottom,
+        )
+        .style(iced::theme::Container::Box)
+        .into()
+    }
+
+    fn channel_metadata_editor(&self) -> Column<'_, Message> {
+        let field_input = |index: usize, field: MetadataField, placeholder: &str, portion: u16| {
+            text_input(
+                placeholder,
+                &self.model.channel_data[index].metadata.field_text(field),
+            )
+            .on_input(move |text| Message::EditChannelMetadata(index, field, text))
+            .width(Length::FillPortion(portion))
+        };
+
+        let mut editor = Column::new().spacing(5).push(text("Channels"));
+        for index in 0..self.model.channel_data.len() {
+            let channel_info = &self.model.channel_data[index];
+            let mut swatch = text("■");
+            if let Some(color) = channel_info.label_color() {
+                swatch = swatch.style(color);
+            }
+            editor = editor.push(
+                Row::new()
+                    .spacing(10)
+                    .align_items(iced::Alignment::Center)
+                    .push(text(format!("Channel {}", index + 1)).width(Length::FillPortion(2)))
+                    .push(field_input(index, MetadataField::Name, "Name", 3))
+                    .push(field_input(index, MetadataField::Unit, "Unit", 1))
+                    .push(field_input(
+                        index,
+                        MetadataField::Description,
+                        "Description",
+                        5,
+                    ))
+                    .push(field_input(index, MetadataField::Color, "#rrggbb", 2))
+                    .push(swatch)
+                    .push(field_input(
+                        index,
+                        MetadataField::DisplayOrder,
+                        &(index + 1).to_string(),
+                        1,
+                    )),
+            );
+        }
+        editor
+    }
+
+    fn navigation_panel(&self) -> Row<'_, Message> {
+        let back_button = button(text("Back"));
+        let back_button = if self.model.navigation.can_go_back() {
+            back_button.on_press(Message::NavigateBack)
+        } else {
+            back_button
+        };
+        let forward_button = button(text("Forward"));
+        let forward_button = if self.model.navigation.can_go_forward() {
+            forward_button.on_press(Message::NavigateForward)
+        } else {
+            forward_button
+        };
+
+        let mut entries = Row::new().spacing(5);
+        for (position, &channel_index) in self.model.navigation.entries().iter().enumerate() {
+            let style = if self.model.navigation.cursor() == Some(position) {
+                iced::theme::Button::Primary
+            } else {
+                iced::theme::Button::Secondary
+            };
+            entries = entries.push(
+                button(text((channel_index + 1).to_string()))
+                    .on_press(Message::JumpToNavigationEntry(position))
+                    .style(style),
+            );
+        }
+
+        Row::new()
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .push(back_button)
+            .push(forward_button)
+            .push(text("History:"))
+            .push(scrollable(entries.padding([0, 0, 10, 0])).direction(
+                scrollable::Direction::Horizontal(scrollable::Properties::default()),
+            ))
+    }
+
+    fn event_log_panel(&self) -> Column<'_, Message> {
+        let mut events = Column::new().spacing(5);
+        for event in self.model.event_log.events().rev() {
+            let mut row = Row::new()
+                .spacing(10)
+                .align_items(iced::Alignment::Center)
+                .push(text(event.describe()).width(Length::Fill));
+            row = if event.acknowledged {
+                row.push(text("Acknowledged"))
+            } else {
+                row.push(button(text("Ack")).on_press(Message::AcknowledgeEvent(event.id)))
+            };
+            events = events.push(row);
+        }
+
+        let controls = Row::new()
+            .spacing(10)
+            .push(button(text("Acknowledge all")).on_press(Message::AcknowledgeAllEvents))
+            .push(button(text("Clear acknowledged")).on_press(Message::ClearAcknowledgedEvents));
+
+        Column::new()
+            .spacing(10)
+            .push(text(format!(
+                "Events ({} unacknowledged)",
+                self.model.event_log.unacknowledged_count()
+            )))
+            .push(controls)
+            .push(scrollable(events).height(Length::Fill))
+    }
+
+    fn notification_history_panel(&self) -> Column<'_, Message> {
+        let palette = self.theme_choice.palette();
+        let mut entries = Column::new().spacing(5);
+        for toast in self.notifications.history().rev() {
+            entries = entries.push(
+                Row::new()
+                    .spacing(10)
+                    .push(text(format_time_of_day(toast.created)))
+                    .push(
+                        text(toast.severity.to_string())
+                            .style(rgb_color(toast.severity.color(&palette))),
+                    )
+                    .push(text(&toast.text).width(Length::Fill)),
+            );
+        }
+
+        Column::new()
+            .spacing(10)
+            .push(text(format!(
+                "Notifications ({})",
+                self.notifications.history_len()
+            )))
+            .push(button(text("Clear history")).on_press(Message::ClearNotificationHistory))
+            .push(scrollable(entries).height(Length::Fill))
+    }
+
+    // Recomputed on every view, so it always matches the values and suspicious flags shown
+    fn statistics_panel(&self) -> Column<'_, Message> {
+        let mut panel = Column::new().spacing(5);
+        let kind = &self.value_kind;
+        if let Some(statistics) = ChannelStatistics::compute(&self.model.channel_data) {
+            panel = panel.push(text(format!(
+                "Min {}  Max {}  Mean {:.1}  Std dev {:.1}  Median {:.1}  Suspicious {}/{}",
+                kind.format_scalar(statistics.min),
+                kind.format_scalar(statistics.max),
+                statistics.mean,
+                statistics.std_dev,
+                statistics.median,
+                statistics.suspicious_count,
+                statistics.channels_count
+            )));
+        }
+
+        let mut running_ranges = Row::new().spacing(15).push(text("Since start:"));
+        for (index, channel_info) in self.model.channel_data.iter().enumerate() {
+            if let (Some(min), Some(max)) = (channel_info.running_min, channel_info.running_max) {
+                running_ranges = running_ranges.push(text(format!(
+                    "{}: {}..{}",
+                    channel_info.metadata.label(index),
+                    kind.format_scalar(min),
+                    kind.format_scalar(max)
+                )));
+            }
+        }
+
+        panel.push(scrollable(running_ranges.padding([0, 0, 10, 0])).direction(
+            scrollable::Direction::Horizontal(scrollable::Properties::default()),
+        ))
+    }
+
+    fn overview_panel(&self) -> Column<'_, Message> {
+        let visible_channels = self.overview.visible_channels(&self.model.channel_data);
+        let range = self.value_kind.range();
+
+        let filters = Row::new()
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .push(checkbox(
+                "Suspicious only",
+                self.overview.suspicious_only,
+                Message::OverviewSuspiciousOnly,
+            ))
+            .push(text("Value from"))
+            .push(
+                text_input(
+                    &self.value_kind.format_scalar(*range.start()),
+                    &self.overview.min_value_text,
+                )
+                .on_input(Message::OverviewMinValueChanged)
+                .width(80),
+            )
+            .push(text("to"))
+            .push(
+                text_input(
+                    &self.value_kind.format_scalar(*range.end()),
+                    &self.overview.max_value_text,
+                )
+                .on_input(Message::OverviewMaxValueChanged)
+                .width(80),
+            )
+            .push(text(format!(
+                "Showing {} of {} channels",
+                visible_channels.len(),
+                self.model.channel_data.len()
+            )));
+
+        let header_button = |label: &str, column: OverviewColumn| {
+            let label = if self.overview.sort_column == column {
+                format!("{label} {}", self.overview.sort_direction.as_symbol())
+            } else {
+                label.to_string()
+            };
+            button(text(label))
+                .on_press(Message::SortOverview(column))
+                .style(iced::theme::Button::Secondary)
+                .width(Length::FillPortion(1))
+        };
+        let header = Row::new()
+            .spacing(10)
+            .push(header_button("Channel", OverviewColumn::Channel))
+            .push(header_button("Value", OverviewColumn::Value))
+            .push(header_button("Suspicious", OverviewColumn::Suspicious));
+
+        let mut rows = Column::new().spacing(2);
+        for index in visible_channels {
+            let channel_info = &self.model.channel_data[index];
+            let mut label = text(channel_info.metadata.label(index));
+            if let Some(color) = channel_info.label_color() {
+                label = label.style(color);
+            }
+            let style = if self.model.navigation.current() == Some(index) {
+                iced::theme::Button::Primary
+            } else {
+                iced::theme::Button::Text
             };
-            self.channel_data[i] = channel_info;
+            rows = rows.push(
+                button(
+                    Row::new()
+                        .spacing(10)
+                        .push(label.width(Length::FillPortion(1)))
+                        .push(
+                            text(channel_info.value_as_text(&self.value_kind))
+                                .width(Length::FillPortion(1)),
+                        )
+                        .push(
+                            text(
+                                channel_info
+                                    .suspicious_as_text(self.model.current_suspicious_limit),
+                            )
+                            .style(self.status_color(channel_info))
+                            .width(Length::FillPortion(1)),
+                        ),
+                )
+                .on_press(Message::ButtonPressed(index + 1))
+                .style(style)
+                .width(Length::Fill),
+            );
+        }
+
+        Column::new()
+            .spacing(10)
+            .push(filters)
+            .push(header)
+            .push(scrollable(rows).height(Length::Fill))
+    }
+
+    fn settings_tab(&self) -> Column<'_, Message> {
+        let mut shortcuts = Column::new().spacing(5).push(text("Keyboard shortcuts"));
+        for (combo, action) in self.key_bindings.bindings() {
+            shortcuts = shortcuts.push(text(format!("{combo}: {action}")));
+        }
+
+        Column::new()
+            .spacing(20)
+            .push(self.theme_section())
+            .push(scrollable(self.channel_metadata_editor()).height(Length::FillPortion(2)))
+            .push(self.rules_overview())
+            .push(shortcuts)
+    }
+
+    fn rules_overview(&self) -> Column<'_, Message> {
+        let explain_all = |rules: &[SuspiciousRule]| {
+            rules
+                .iter()
+                .map(|rule| rule.explain(self.model.current_suspicious_limit))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut overview = Column::new()
+            .spacing(10)
+            .push(text("Suspicious rules"))
+            .push(text(format!(
+                "All channels: {}",
+                explain_all(&self.model.rules.global)
+            )));
+
+        let mut overridden_channels: Vec<_> =
+            self.model.rules.per_channel.keys().copied().collect();
+        overridden_channels.sort_unstable();
+        for index in overridden_channels {
+            overview = overview.push(text(format!(
+                "Channel {}: {}",
+                index + 1,
+                explain_all(self.model.rules.rules_for(index))
+            )));
         }
+
+        overview
     }
 
-    fn update_suspicious(&mut self) {
-        for data in &mut self.channel_data {
-            data.is_suspicious = data.integer_value > self.current_suspicious_limit;
+    fn history_button<'a>(
+        &self,
+        label: &'a str,
+        message: Message,
+        is_enabled: bool,
+    ) -> Button<'a, Message> {
+        let button = button(text(label)).style(iced::theme::Button::Secondary);
+        if is_enabled {
+            button.on_press(message)
+        } else {
+            button
         }
     }
 
@@ -108,221 +1551,357 @@ impl ChannelBasedApp {
         };
         button.style(style)
     }
-}
-
-impl Sandbox for ChannelBasedApp {
-    type Message = Message;
-
-    fn new() -> Self {
-        let mut app = ChannelBasedApp {
-            previous_channel_index: INVALID_CHANNEL_INDEX,
-            current_channel_index: INVALID_CHANNEL_INDEX,
-            channel_data: Default::default(),
-            active_tab: ApplicationTab::Home,
-            current_suspicious_limit: SUSPICIOUS_LIMIT,
-        };
-
-        // TODO it might be separated button, Initialize
-        app.init_data();
-        app.update(Message::ButtonPressed(BACKUP_CHANNEL_INDEX + 1));
-
-        app
-    }
-
-    fn title(&self) -> String {
-        String::from("Some App with Channels")
-    }
 
-    fn update(&mut self, message: Message) {
+    fn handle_message(&mut self, message: Message) -> Command<Message> {
         match message {
             Message::IgnoreInput => {}
             Message::TabSelected(tab) => {
                 self.active_tab = tab;
             }
-            Message::ButtonPressed(index) => {
-                if self.current_channel_index != INVALID_CHANNEL_INDEX {
-                    self.previous_channel_index = self.current_channel_index;
-                }
-
-                self.current_channel_index = index - 1;
+            Message::ButtonPressed(channel) => {
+                channel_model::update(&mut self.model, ChannelMessage::SelectChannel(channel));
             }
             Message::ChangeChannel(change) => {
-                if self.current_channel_index == INVALID_CHANNEL_INDEX {
-                    self.update(Message::ButtonPressed(BACKUP_CHANNEL_INDEX + 1));
-                    return;
-                }
-
-                self.previous_channel_index = self.current_channel_index;
-                let new_channel_index =
-                    ((self.current_channel_index as i32 + change).rem_euclid(9)) as usize;
-                self.current_channel_index = new_channel_index;
+                channel_model::update(&mut self.model, ChannelMessage::ChangeChannel(change));
             }
             Message::ClearChannelRow(selected_row) => {
-                if selected_row == ChannelDataRow::Previous {
-                    self.previous_channel_index = INVALID_CHANNEL_INDEX;
-                } else if selected_row == ChannelDataRow::Current {
-                    self.current_channel_index = INVALID_CHANNEL_INDEX;
-                } else {
-                    panic!("Unexpected ChannelDataRow value!!")
+                channel_model::update(&mut self.model, ChannelMessage::ClearRow(selected_row));
+            }
+            Message::NavigateBack => {
+                channel_model::update(&mut self.model, ChannelMessage::NavigateBack);
+            }
+            Message::NavigateForward => {
+                channel_model::update(&mut self.model, ChannelMessage::NavigateForward);
+            }
+            Message::JumpToNavigationEntry(position) => {
+                channel_model::update(
+                    &mut self.model,
+                    ChannelMessage::JumpToNavigationEntry(position),
+                );
+            }
+            Message::ModifyingChannelsCount(new_count) => {
+                channel_model::update(
+                    &mut self.model,
+                    ChannelMessage::ModifyingChannelsCount(new_count),
+                );
+            }
+            Message::ReleasedChannelsCountSlider => {
+                let old_count = self.model.channels_count();
+                channel_model::update(&mut self.model, ChannelMessage::ReleasedChannelsCountSlider);
+                let channels_count = self.model.channels_count();
+                self.pinned_channels.retain(|&index| index < channels_count);
+                // Fill the new channels with real values right away
+                if self.model.channels_count() != old_count {
+                    return self.pull_sample();
                 }
             }
             Message::ModifyingSuspiciosValue(new_value) => {
-                self.current_suspicious_limit = new_value;
+                channel_model::update(
+                    &mut self.model,
+                    ChannelMessage::ModifyingSuspiciousLimit(
+                        self.value_kind.round_to_step(new_value),
+                    ),
+                );
             }
             Message::ReleasedSuspiciousSlider => {
-                self.update_suspicious();
+                channel_model::update(&mut self.model, ChannelMessage::ReleasedSuspiciousSlider);
+            }
+            Message::SampleTick => {
+                return self.pull_sample();
+            }
+            Message::SampleReceived(values) => {
+                self.is_sample_pending = false;
+                channel_model::update(&mut self.model, ChannelMessage::SampleReceived(values));
+            }
+            // A source that keeps failing shows its error once while the toast is up
+            Message::SampleFailed(err) => {
+                self.is_sample_pending = false;
+                self.notifications.push_unique(Severity::Error, err);
+            }
+            Message::ModifyingSampleInterval(new_interval_ms) => {
+                self.sample_interval = Duration::from_millis(new_interval_ms as u64);
+            }
+            Message::ToggleStreaming => {
+                self.is_streaming_paused = !self.is_streaming_paused;
+            }
+            Message::ToggleEventLog => {
+                self.is_event_log_visible = !self.is_event_log_visible;
+            }
+            Message::AcknowledgeEvent(id) => {
+                channel_model::update(&mut self.model, ChannelMessage::AcknowledgeEvent(id));
+            }
+            Message::AcknowledgeAllEvents => {
+                channel_model::update(&mut self.model, ChannelMessage::AcknowledgeAllEvents);
+            }
+            Message::ClearAcknowledgedEvents => {
+                channel_model::update(&mut self.model, ChannelMessage::ClearAcknowledgedEvents);
+            }
+            Message::DataFilePathChanged(path) => {
+                self.data_file_path = path;
+            }
+            Message::ExportSnapshot(format) => {
+                let path = Path::new(&self.data_file_path).with_extension(format.extension());
+                self.file_errors.clear();
+                let (severity, status) = match self.export_snapshot(&path, format) {
+                    Ok(written) => (
+                        Severity::Success,
+                        format!(
+                            "Exported {}",
+                            written
+                                .iter()
+                                .map(|path| path.display().to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ),
+                    ),
+                    Err(err) => (
+                        Severity::Error,
+                        format!("Export to {} failed: {err}", path.display()),
+                    ),
+                };
+                self.notifications.push(severity, status.clone());
+                self.file_status = Some(status);
+            }
+            Message::ImportSnapshot(format) => {
+                let path = Path::new(&self.data_file_path).with_extension(format.extension());
+                return self.import_snapshot(&path, format);
+            }
+            Message::ValuesImported(values) => {
+                channel_model::update(&mut self.model, ChannelMessage::ValuesImported(values));
+                // Keep the imported values on screen instead of overwriting them with the next sample
+                self.is_streaming_paused = true;
+            }
+            Message::CloseRequested => {
+                self.save_palette();
+                if let Some(path) = &self.session_path {
+                    if let Err(err) = self.session_state().save(path) {
+                        eprintln!("Can't save session to {}: {err}", path.display());
+                    }
+                }
+                return window::close();
+            }
+            Message::RegenerateData => {
+                return self.pull_sample();
+            }
+            Message::JumpToFirstSuspicious => {
+                channel_model::update(&mut self.model, ChannelMessage::JumpToFirstSuspicious);
+            }
+            Message::ToggleOverview => {
+                self.is_overview_visible = !self.is_overview_visible;
+            }
+            Message::SortOverview(column) => {
+                self.overview.sort_by(column);
+            }
+            Message::OverviewSuspiciousOnly(suspicious_only) => {
+                self.overview.suspicious_only = suspicious_only;
+            }
+            Message::OverviewMinValueChanged(min_value) => {
+                self.overview.min_value_text = min_value;
+            }
+            Message::OverviewMaxValueChanged(max_value) => {
+                self.overview.max_value_text = max_value;
+            }
+            Message::EditChannelMetadata(index, field, text) => {
+                channel_model::update(
+                    &mut self.model,
+                    ChannelMessage::EditMetadata(index, field, text),
+                );
+            }
+            Message::Undo => {
+                channel_model::update(&mut self.model, ChannelMessage::Undo);
+            }
+            Message::Redo => {
+                channel_model::update(&mut self.model, ChannelMessage::Redo);
+            }
+            Message::ThemeSelected(choice) => {
+                self.theme_choice = choice;
+                self.is_palette_unsaved = false;
+                self.save_theme();
+            }
+            Message::PaletteColorChanged(which, text) => {
+                if let Some(position) = PaletteColor::ALL.iter().position(|&w| w == which) {
+                    self.palette_inputs[position] = text.clone();
+                }
+                if let Some(rgb) = app_theme::parse_hex_color(&text) {
+                    self.custom_palette.set_color(which, rgb);
+                    self.theme_choice = ThemeChoice::Custom(self.custom_palette);
+                    self.is_palette_unsaved = true;
+                }
+            }
+            Message::PaletteColorSubmitted => {
+                self.save_palette();
+            }
+            // Resolved into the messages they stand for before reaching here
+            Message::KeyPressed(_) | Message::RunActionButton(_) => {}
+            Message::PinChannel(index) => {
+                if index < self.model.channels_count() && !self.pinned_channels.contains(&index) {
+                    self.pinned_channels.push(index);
+                }
+            }
+            Message::UnpinChannel(index) => {
+                self.pinned_channels.retain(|&pinned| pinned != index);
             }
+            Message::ToggleEditMode => {
+                self.is_edit_mode = !self.is_edit_mode;
+                self.value_edits.clear();
+            }
+            Message::ValueEdited(index, text) => {
+                self.value_edits.insert(index, text);
+            }
+            // Invalid text stays in the cell with its error shown below it
+            Message::ApplyValueEdit(index) => {
+                let Some(text) = self.value_edits.get(&index) else {
+                    return Command::none();
+                };
+                if let Ok(value) = self
+                    .value_kind
+                    .parse_in_range(text, self.value_kind.range())
+                {
+                    self.value_edits.remove(&index);
+                    channel_model::update(
+                        &mut self.model,
+                        ChannelMessage::OverrideValue(index, value),
+                    );
+                }
+            }
+            Message::ClearOverride(index) => {
+                channel_model::update(&mut self.model, ChannelMessage::ClearOverride(index));
+            }
+            Message::ReplayTick => {
+                return self.replay_due_messages();
+            }
+            Message::ExpireToasts => {
+                self.notifications.expire(Instant::now());
+            }
+            Message::DismissToast(id) => self.notifications.dismiss(id),
+            Message::ToggleNotificationHistory => {
+                self.is_notification_history_visible = !self.is_notification_history_visible;
+            }
+            Message::ClearNotificationHistory => self.notifications.clear_history(),
         }
+
+        Command::none()
     }
+}
 
-    fn view(&self) -> Element<Message> {
-        let mut previous_value_text = String::new();
-        let mut previous_suspicious_text = String::new();
-        let mut previous_channel_text = String::new();
+impl Application for ChannelBasedApp {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
 
-        if self.previous_channel_index != INVALID_CHANNEL_INDEX {
-            previous_value_text = self.channel_data[self.previous_channel_index].value_as_text();
-            previous_suspicious_text =
-                self.channel_data[self.previous_channel_index].suspicious_as_text();
-            previous_channel_text = (self.previous_channel_index + 1).to_string();
-        }
-        let mut current_value_text = String::new();
-        let mut current_suspicious_text = String::new();
-        let mut current_channel_text = String::new();
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        ChannelBasedApp::with_options(StartupOptions::from_startup_args())
+    }
+
+    fn title(&self) -> String {
+        String::from("Some App with Channels")
+    }
+
+    fn theme(&self) -> Theme {
+        iced_theme(&self.theme_choice)
+    }
 
-        if self.current_channel_index != INVALID_CHANNEL_INDEX {
-            current_value_text = self.channel_data[self.current_channel_index].value_as_text();
-            current_suspicious_text =
-                self.channel_data[self.current_channel_index].suspicious_as_text();
-            current_channel_text = (self.current_channel_index + 1).to_string();
+    fn update(&mut self, message: Message) -> Command<Message> {
+        let mut commands = Vec::new();
+        for message in self.resolve(message) {
+            if message.is_recorded() {
+                if let Some(recorder) = &mut self.recorder {
+                    if let Err(err) = recorder.record(&message) {
+                        eprintln!("Recording stopped: {err}");
+                        self.recorder = None;
+                    }
+                }
+            }
+            commands.push(self.handle_message(message));
         }
-        let table = Row::new()
-            .spacing(5)
-            .push(
-                Column::new()
-                    .width(Length::FillPortion(1)) // Ensure equal width
-                    .spacing(10)
-                    .align_items(iced::Alignment::End)
-                    .push(Space::with_height(Length::FillPortion(1)))
-                    .push(text("Previous:").height(Length::FillPortion(2)))
-                    .push(text("Current:").height(Length::FillPortion(2))),
-            )
-            .push(
-                Column::new()
-                    .width(Length::FillPortion(2)) // Ensure equal width
-                    .spacing(10)
-                    .align_items(iced::Alignment::Center)
-                    .push(text("Value").height(Length::FillPortion(1)))
-                    .push(
-                        Container::new(
-                            text_input("Previous Value", &previous_value_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    )
-                    .push(
-                        Container::new(
-                            text_input("Current Value", &current_value_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    ),
-            )
-            .push(
-                Column::new()
-                    .width(Length::FillPortion(2)) // Ensure equal width
-                    .spacing(10)
-                    .align_items(iced::Alignment::Center)
-                    .push(text("Suspicious").height(Length::FillPortion(1)))
-                    .push(
-                        Container::new(
-                            text_input("Suspicious?", &previous_suspicious_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    )
-                    .push(
-                        Container::new(
-                            text_input("Suspicious?", &current_suspicious_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    ),
-            )
-            .push(
-                Column::new()
-                    .width(Length::FillPortion(2)) // Ensure equal width
-                    .spacing(10)
-                    .align_items(iced::Alignment::Center)
-                    .push(text("Channel").height(Length::FillPortion(1)))
-                    .push(
-                        Container::new(
-                            text_input("Channel", &previous_channel_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    )
-                    .push(
-                        Container::new(
-                            text_input("Channel", &current_channel_text)
-                                .on_input(move |_| Message::IgnoreInput), // to be in 'enabled' state
-                        )
-                        .height(Length::FillPortion(2))
-                        .width(Length::Fill),
-                    ),
-            )
-            .push(
-                Column::new()
-                    .width(Length::FillPortion(2)) // Ensure equal width
-                    .spacing(10)
-                    .align_items(iced::Alignment::Center)
-                    .push(text("Actions").height(Length::FillPortion(1)))
-                    .push(
-                        button(text("Clear Previous"))
-                            .on_press(Message::ClearChannelRow(ChannelDataRow::Previous))
-                            .height(Length::FillPortion(1)),
-                    )
-                    .push(Space::with_height(Length::FillPortion(1)))
-                    .push(
-                        button(text("Clear Current"))
-                            .on_press(Message::ClearChannelRow(ChannelDataRow::Current))
-                            .height(Length::FillPortion(1)),
-                    )
-                    .push(Space::with_height(Length::FillPortion(1))),
+        self.notify_suspicious_changes();
+        Command::batch(commands)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // Key presses captured by a widget (e.g. typing into a text input) aren't shortcuts
+        let events = subscription::events_with(|event, status| match (event, status) {
+            (Event::Window(window::Event::CloseRequested), _) => Some(Message::CloseRequested),
+            (
+                Event::Keyboard(keyboard::Event::KeyPressed {
+                    key_code,
+                    modifiers,
+                }),
+                event::Status::Ignored,
+            ) => key_combo(key_code, modifiers).map(Message::KeyPressed),
+            _ => None,
+        });
+
+        let mut subscriptions = vec![events];
+        if self.notifications.next_timeout(Instant::now()).is_some() {
+            subscriptions.push(
+                time::every(Duration::from_millis(TOAST_TICK_MS)).map(|_| Message::ExpireToasts),
             );
+        }
+
+        // Recorded samples replace live ones, also after the replay so its result stays shown
+        if self.replay.is_some() {
+            if self.is_replaying() {
+                subscriptions.push(
+                    time::every(Duration::from_millis(REPLAY_TICK_MS)).map(|_| Message::ReplayTick),
+                );
+            }
+        } else if !self.is_streaming_paused {
+            subscriptions.push(time::every(self.sample_interval).map(|_| Message::SampleTick));
+        }
+
+        Subscription::batch(subscriptions)
+    }
+
+    fn view(&self) -> Element<'_, Message> {
+        let table = self.comparison_table();
 
         let separator = Rule::horizontal(20);
 
+        let mut channels_in_display_order: Vec<_> = (0..self.model.channel_data.len()).collect();
+        channels_in_display_order
+            .sort_by_key(|&index| self.model.channel_data[index].metadata.sort_key(index));
+
         let mut buttons_row = Row::new().spacing(10);
-        for i in 0..CHANNELS_COUNT {
-            let label = (i + 1).to_string();
-            let button = button(text(label))
-                .on_press(Message::ButtonPressed(i + 1))
-                .padding(if self.current_channel_index == i {
-                    20
-                } else {
-                    10
-                });
-            buttons_row = buttons_row.push(button);
+        for index in channels_in_display_order {
+            buttons_row = buttons_row.push(self.channel_button(index));
         }
 
-        // dummies for now
-        let wider_buttons = Row::new()
+        let buttons_row = scrollable(buttons_row.padding([0, 0, 10, 0])).direction(
+            scrollable::Direction::Horizontal(scrollable::Properties::default()),
+        );
+
+        let channels_count_slider = slider(
+            MIN_CHANNELS_COUNT as u32..=MAX_CHANNELS_COUNT as u32,
+            self.model.requested_channels_count as u32,
+            Message::ModifyingChannelsCount,
+        )
+        .step(1)
+        .on_release(Message::ReleasedChannelsCountSlider);
+
+        let channels_count_section = Row::new()
             .spacing(10)
-            .push(button(text("Wide Button 1")))
-            .push(button(text("Wide Button 2")));
+            .align_items(iced::Alignment::Center)
+            .push(text(format!(
+                "Channels: {}",
+                self.model.requested_channels_count
+            )))
+            .push(channels_count_slider);
+
+        let mut wider_buttons = Row::new().spacing(10);
+        for (button_index, action_button) in self.action_bar.iter().enumerate() {
+            wider_buttons = wider_buttons.push(
+                button(text(&action_button.label)).on_press(Message::RunActionButton(button_index)),
+            );
+        }
 
         let suspicios_limit_slider = slider(
-            LOW_INTEGER_LIMIT..=HIGH_INTEGER_LIMIT,
-            self.current_suspicious_limit,
+            self.value_kind.range(),
+            self.model.current_suspicious_limit,
             Message::ModifyingSuspiciosValue,
         )
-        .step(1)
+        .step(self.value_kind.step())
         .on_release(Message::ReleasedSuspiciousSlider);
 
         let suspicious_limit_section = Row::new()
@@ -332,25 +1911,90 @@ impl Sandbox for ChannelBasedApp {
                     .spacing(10),
             )
             .push(Space::with_width(10))
-            .push(Column::new().push(text(self.current_suspicious_limit.to_string())))
+            .push(
+                Column::new().push(text(
+                    self.value_kind
+                        .format_scalar(self.model.current_suspicious_limit),
+                )),
+            )
             .push(Space::with_width(10))
             .push(Column::new().push(suspicios_limit_slider).spacing(10));
 
+        let sample_interval_ms = self.sample_interval.as_millis() as u32;
+        let sample_interval_slider = slider(
+            MIN_SAMPLE_INTERVAL_MS..=MAX_SAMPLE_INTERVAL_MS,
+            sample_interval_ms.clamp(MIN_SAMPLE_INTERVAL_MS, MAX_SAMPLE_INTERVAL_MS),
+            Message::ModifyingSampleInterval,
+        )
+        .step(100);
+
+        let streaming_section = Row::new()
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .push(text(format!("Sample every {sample_interval_ms} ms:")))
+            .push(sample_interval_slider)
+            .push(
+                button(text(if self.is_streaming_paused {
+                    "Resume"
+                } else {
+                    "Pause"
+                }))
+                .on_press(Message::ToggleStreaming),
+            );
+
+        let mut file_section = Column::new().spacing(5).push(
+            Row::new()
+                .spacing(10)
+                .align_items(iced::Alignment::Center)
+                .push(text("Data file:"))
+                .push(
+                    text_input(DEFAULT_DATA_FILE_NAME, &self.data_file_path)
+                        .on_input(Message::DataFilePathChanged),
+                )
+                .push(
+                    button(text("Export CSV")).on_press(Message::ExportSnapshot(ExportFormat::Csv)),
+                )
+                .push(
+                    button(text("Export JSON"))
+                        .on_press(Message::ExportSnapshot(ExportFormat::Json)),
+                )
+                .push(
+                    button(text("Import CSV")).on_press(Message::ImportSnapshot(ExportFormat::Csv)),
+                )
+                .push(
+                    button(text("Import JSON"))
+                        .on_press(Message::ImportSnapshot(ExportFormat::Json)),
+                ),
+        );
+        if let Some(status) = &self.file_status {
+            file_section = file_section.push(text(status));
+        }
+        for error in &self.file_errors {
+            file_section = file_section.push(text(error));
+        }
+
         let arrows = Row::new()
             .spacing(10)
             .push(button(text("<")).on_press(Message::ChangeChannel(-1)))
             .push(button(text(">")).on_press(Message::ChangeChannel(1)));
 
+        let navigation_section = self.navigation_panel();
+
         let main_content = Column::new()
             .align_items(iced::Alignment::Center)
             .spacing(10)
             .padding(80)
-            .push(table.height(Length::FillPortion(2)))
+            .push(Container::new(table).height(Length::FillPortion(2)))
             .push(separator)
             .push(buttons_row.height(Length::FillPortion(1)))
             .push(arrows.height(Length::FillPortion(1)))
+            .push(navigation_section)
             .push(wider_buttons.height(Length::FillPortion(1)))
+            .push(channels_count_section)
             .push(suspicious_limit_section)
+            .push(self.statistics_panel())
+            .push(streaming_section)
+            .push(file_section)
             .height(Length::FillPortion(1));
 
         let tab_row = Row::new()
@@ -358,16 +2002,101 @@ impl Sandbox for ChannelBasedApp {
             .width(Length::Fill) // Make the row take the full width
             .align_items(iced::Alignment::Start)
             .push(self.tab_button("Main", &ApplicationTab::Home))
-            .push(self.tab_button("Dummy", &ApplicationTab::Settings))
-            .push(self.tab_button("About", &ApplicationTab::About));
+            .push(self.tab_button("Settings", &ApplicationTab::Settings))
+            .push(self.tab_button("About", &ApplicationTab::About))
+            .push(Space::with_width(Length::Fill))
+            .push(self.history_button("Undo", Message::Undo, self.model.can_undo()))
+            .push(self.history_button("Redo", Message::Redo, self.model.can_redo()))
+            .push(
+                button(text(if self.is_edit_mode {
+                    "Stop editing"
+                } else {
+                    "Edit values"
+                }))
+                .on_press(Message::ToggleEditMode)
+                .style(iced::theme::Button::Secondary),
+            )
+            .push(
+                button(text(if self.is_overview_visible {
+                    "Hide overview"
+                } else {
+                    "Show overview"
+                }))
+                .on_press(Message::ToggleOverview)
+                .style(iced::theme::Button::Secondary),
+            )
+            .push(
+                button(text(if self.is_event_log_visible {
+                    "Hide events"
+                } else {
+                    "Show events"
+                }))
+                .on_press(Message::ToggleEventLog)
+                .style(iced::theme::Button::Secondary),
+            )
+            .push(
+                button(text(if self.is_notification_history_visible {
+                    "Hide notifications"
+                } else {
+                    "Notifications"
+                }))
+                .on_press(Message::ToggleNotificationHistory)
+                .style(iced::theme::Button::Secondary),
+            );
 
         let content = match self.active_tab {
+            ApplicationTab::Home if self.is_overview_visible => self.overview_panel(),
             ApplicationTab::Home => main_content,
-            ApplicationTab::Settings => Column::new().push(text("Dummy Tab Content")),
-            ApplicationTab::About => Column::new().push(text("About Tab Content")),
+            ApplicationTab::Settings => self.settings_tab(),
+            ApplicationTab::About => {
+                let mut about = Column::new()
+                    .spacing(10)
+                    .push(text("About Tab Content"))
+                    .push(text(format!(
+                        "Data source: {}",
+                        self.data_source_description
+                    )));
+                if let Some(status) = self.replay_status() {
+                    about = about.push(text(status));
+                }
+                if let Some(seed) = self.seed {
+                    // Read-only input so the seed can be copied into a bug report
+                    about = about.push(
+                        Row::new()
+                            .spacing(10)
+                            .align_items(iced::Alignment::Center)
+                            .push(text("Seed:"))
+                            .push(
+                                text_input("Seed", &seed.to_string())
+                                    .on_input(|_| Message::IgnoreInput)
+                                    .width(200),
+                            ),
+                    );
+                }
+                about
+            }
         };
 
-        Container::new(
+        // The event log lives next to the tab content so it stays visible on every tab
+        let mut body = Row::new()
+            .spacing(20)
+            .push(Container::new(content).width(Length::FillPortion(3)));
+        if self.is_event_log_visible {
+            body = body.push(
+                Container::new(self.event_log_panel())
+                    .width(Length::FillPortion(2))
+                    .height(Length::Fill),
+            );
+        }
+        if self.is_notification_history_visible {
+            body = body.push(
+                Container::new(self.notification_history_panel())
+                    .width(Length::FillPortion(2))
+                    .height(Length::Fill),
+            );
+        }
+
+        let window_content = Container::new(
             Column::new()
                 .push(
                     Container::new(tab_row)
@@ -375,12 +2104,367 @@ impl Sandbox for ChannelBasedApp {
                         .align_y(iced::alignment::Vertical::Top)
                         .center_x(),
                 )
-                .push(content)
+                .push(body)
                 .spacing(20)
                 .padding(20),
         )
         .width(Length::Fill)
-        .height(Length::Fill)
-        .into()
+        .height(Length::Fill);
+
+        toast_overlay::view(
+            window_content,
+            self.notifications.visible(),
+            &self.theme_choice.palette(),
+            Message::DismissToast,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::channel_data_source::RandomDataSource;
+    use crate::channel_history::DEFAULT_HISTORY_CAPACITY;
+    use crate::channel_metadata::ChannelMetadata;
+    use crate::key_bindings::KEY_NAMES;
+    use crate::message_recorder::RecordedMessage;
+    use crate::test_util::TempDir;
+
+    // No session, theme file, recording or command line, just seeded random values
+    fn test_app() -> ChannelBasedApp {
+        let value_kind = ValueKind::Unsigned;
+        let (app, _) = ChannelBasedApp::with_options(StartupOptions {
+            channels_count: DEFAULT_CHANNELS_COUNT,
+            history_capacity: DEFAULT_HISTORY_CAPACITY,
+            rules: RuleSet::default(),
+            key_bindings: KeyBindings::retained_defaults(),
+            action_bar: Vec::new(),
+            data_source: Box::new(RandomDataSource::new(value_kind.clone(), 7)),
+            value_kind,
+            sample_interval: Duration::from_secs(1),
+            session_path: None,
+            theme_choice: ThemeChoice::default(),
+            theme_path: None,
+            replay: None,
+            record_path: None,
+        });
+        app
+    }
+
+    #[test]
+    fn iced_keys_get_the_names_used_by_key_bindings() {
+        use keyboard::{KeyCode, Modifiers};
+
+        assert_eq!(
+            key_combo(KeyCode::Numpad3, Modifiers::empty()),
+            Some(KeyCombo::new("3", false, false, false))
+        );
+        assert_eq!(
+            key_combo(KeyCode::LBracket, Modifiers::CTRL | Modifiers::SHIFT),
+            Some(KeyCombo::new("openbracket", true, true, false))
+        );
+        assert_eq!(key_combo(KeyCode::Mute, Modifiers::empty()), None);
+        for key_code in [KeyCode::Key7, KeyCode::F12, KeyCode::Delete, KeyCode::Grave] {
+            let combo = key_combo(key_code, Modifiers::empty()).unwrap();
+            assert!(KEY_NAMES.contains(&combo.key.as_str()), "{key_code:?}");
+        }
+    }
+
+    // The seed only says how the recorded app got its samples
+    fn final_state(app: &ChannelBasedApp) -> InitialState {
+        InitialState {
+            seed: None,
+            ..app.initial_state()
+        }
+    }
+
+    #[test]
+    fn headless_replay_reproduces_recorded_session() {
+        let temp_dir = TempDir::new("recording");
+        let path = temp_dir.file("recording.jsonl");
+        let mut recorded_app = test_app();
+        recorded_app.recorder =
+            Some(MessageRecorder::create(&path, &recorded_app.initial_state()).unwrap());
+        let channels_count = recorded_app.model.channels_count();
+
+        for message in [
+            Message::ButtonPressed(3),
+            Message::SampleReceived(
+                (1..=channels_count as u32)
+                    .map(|n| ChannelValue::Unsigned(n * 10 % 100))
+                    .collect(),
+            ),
+            Message::ModifyingSuspiciosValue(40.0),
+            Message::ModifyingSuspiciosValue(30.0),
+            Message::ReleasedSuspiciousSlider,
+            Message::ChangeChannel(1),
+            Message::ValuesImported(vec![
+                (0, ChannelValue::Unsigned(99)),
+                (1, ChannelValue::Unsigned(2)),
+            ]),
+            Message::EditChannelMetadata(0, MetadataField::Name, "Pressure".to_string()),
+            Message::Undo,
+            Message::KeyPressed(KeyCombo::new("2", false, false, false)),
+        ] {
+            let _ = recorded_app.update(message);
+        }
+
+        let recording = Recording::load(&path).unwrap();
+        assert_eq!(recording.messages.len(), 10);
+
+        let mut replayed_app = test_app();
+        replayed_app.apply_initial_state(recording.initial_state);
+        replayed_app.replay = Some(Replay::new(recording.messages, ReplayMode::Headless));
+        replayed_app.finish_replay();
+
+        assert_eq!(final_state(&replayed_app), final_state(&recorded_app));
+        assert_eq!(
+            replayed_app.model.event_log.events().count(),
+            recorded_app.model.event_log.events().count()
+        );
+    }
+
+    #[test]
+    fn keys_and_buttons_are_recorded_as_what_they_stand_for() {
+        let temp_dir = TempDir::new("recording_resolved");
+        let path = temp_dir.file("recording.jsonl");
+        let mut recorded_app = test_app();
+        recorded_app.action_bar = vec![ActionButton {
+            label: "Next two".to_string(),
+            actions: vec![BarAction::SelectChannel(2), BarAction::ChangeChannel(1)],
+        }];
+        recorded_app.recorder =
+            Some(MessageRecorder::create(&path, &recorded_app.initial_state()).unwrap());
+
+        for message in [
+            Message::KeyPressed(KeyCombo::new("4", false, false, false)),
+            Message::RunActionButton(0),
+            Message::SampleTick,
+        ] {
+            let _ = recorded_app.update(message);
+        }
+
+        let recording = Recording::<Message>::load(&path).unwrap();
+        let recorded: Vec<_> = recording
+            .messages
+            .iter()
+            .map(|recorded| format!("{:?}", recorded.message))
+            .collect();
+        assert_eq!(
+            recorded,
+            ["ButtonPressed(4)", "ButtonPressed(2)", "ChangeChannel(1)"]
+        );
+
+        // Replayed without the bindings and bar of the recording
+        let mut replayed_app = test_app();
+        replayed_app
+            .key_bindings
+            .apply_overrides("4=channel:1")
+            .unwrap();
+        replayed_app.apply_initial_state(recording.initial_state);
+        replayed_app.replay = Some(Replay::new(recording.messages, ReplayMode::Headless));
+        replayed_app.finish_replay();
+        assert_eq!(final_state(&replayed_app), final_state(&recorded_app));
+    }
+
+    #[test]
+    fn buttons_replayed_from_old_recordings_skip_side_effects() {
+        let temp_dir = TempDir::new("replay_export");
+        let path = temp_dir.file("export");
+        let mut app = test_app();
+        app.data_file_path = path.display().to_string();
+        app.action_bar = vec![ActionButton {
+            label: "Export".to_string(),
+            actions: vec![BarAction::Export(ExportFormat::Json)],
+        }];
+        app.replay = Some(Replay::new(
+            vec![RecordedMessage {
+                at_ms: 0,
+                message: Message::RunActionButton(0),
+            }],
+            ReplayMode::Headless,
+        ));
+        app.finish_replay();
+
+        let exported = path.with_extension(ExportFormat::Json.extension());
+        assert!(!exported.exists());
+    }
+
+    #[test]
+    fn real_time_recordings_finish_without_waiting() {
+        let mut app = test_app();
+        app.replay = Some(Replay::new(
+            vec![RecordedMessage {
+                at_ms: 60 * 60 * 1000,
+                message: Message::ButtonPressed(3),
+            }],
+            ReplayMode::RealTime,
+        ));
+        app.finish_replay();
+
+        assert!(!app.is_replaying());
+        assert_eq!(app.initial_state().current_channel, Some(3));
+    }
+
+    #[test]
+    fn suspicious_changes_show_toasts() {
+        let mut app = test_app();
+        app.apply_initial_state(InitialState {
+            values: vec![ChannelValue::Unsigned(1); 3],
+            suspicious_limit: 75.0,
+            value_kind: ValueKind::Unsigned,
+            previous_channel: None,
+            current_channel: None,
+            channel_metadata: Vec::new(),
+            seed: None,
+        });
+        app.notifications = Notifications::default();
+        app.last_notified_event = None;
+
+        let sample = |values: [u32; 3]| {
+            Message::SampleReceived(values.into_iter().map(ChannelValue::Unsigned).collect())
+        };
+        let toasts = |app: &ChannelBasedApp| -> Vec<(Severity, String)> {
+            app.notifications
+                .visible()
+                .map(|toast| (toast.severity, toast.text.clone()))
+                .collect()
+        };
+        let _ = app.update(sample([90, 1, 1]));
+        let _ = app.update(sample([90, 1, 1]));
+        assert_eq!(
+            toasts(&app),
+            [(
+                Severity::Warning,
+                "Channel 1 became suspicious (> 75)".to_string()
+            )]
+        );
+
+        // Later changes replace the toast instead of stacking up
+        let _ = app.update(sample([1, 1, 1]));
+        assert_eq!(
+            toasts(&app),
+            [(Severity::Success, "Channel 1 is back to normal".to_string())]
+        );
+        let _ = app.update(sample([90, 1, 80]));
+        let _ = app.update(sample([1, 95, 80]));
+        assert_eq!(
+            toasts(&app),
+            [(
+                Severity::Warning,
+                "Channel 2 became suspicious; Channel 1 back to normal".to_string()
+            )]
+        );
+        let _ = app.update(sample([1, 1, 1]));
+        assert_eq!(
+            toasts(&app),
+            [(
+                Severity::Success,
+                "Channels 2, 3 back to normal".to_string()
+            )]
+        );
+        assert_eq!(app.notifications.history_len(), 1);
+    }
+
+    #[test]
+    fn failed_reads_show_one_error_toast() {
+        let mut app = test_app();
+        app.notifications = Notifications::default();
+        for _ in 0..2 {
+            app.is_sample_pending = true;
+            let _ = app.update(Message::SampleFailed("Failed to read".to_string()));
+            assert!(!app.is_sample_pending);
+        }
+
+        let toasts: Vec<_> = app
+            .notifications
+            .visible()
+            .map(|toast| (toast.severity, toast.text.as_str()))
+            .collect();
+        assert_eq!(toasts, [(Severity::Error, "Failed to read")]);
+    }
+
+    #[test]
+    fn sessions_of_another_channel_count_are_cut_to_fit() {
+        let mut session = test_app().session_state();
+        session.previous_channel = Some(DEFAULT_CHANNELS_COUNT + 1);
+        session.current_channel = Some(2);
+        session.channel_metadata = (1..=DEFAULT_CHANNELS_COUNT + 3)
+            .map(|channel| ChannelMetadata {
+                name: format!("Sensor {channel}"),
+                ..ChannelMetadata::default()
+            })
+            .collect();
+
+        let mut app = test_app();
+        app.restore_session(session.clone());
+        assert_eq!(app.model.navigation.previous(), None);
+        assert_eq!(app.model.navigation.current(), Some(1));
+        assert_eq!(app.model.channel_data.len(), DEFAULT_CHANNELS_COUNT);
+        assert_eq!(
+            app.model.channel_data.last().unwrap().metadata.name,
+            format!("Sensor {DEFAULT_CHANNELS_COUNT}")
+        );
+
+        // Channels the session knows nothing about keep their defaults
+        session.channel_metadata.truncate(2);
+        let mut app = test_app();
+        app.restore_session(session);
+        assert_eq!(app.model.channel_data[1].metadata.name, "Sensor 2");
+        assert_eq!(
+            app.model.channel_data[2].metadata,
+            ChannelMetadata::default()
+        );
+    }
+
+    #[test]
+    fn pinning_ignores_duplicates_and_missing_channels() {
+        let mut app = test_app();
+        for message in [
+            Message::PinChannel(4),
+            Message::PinChannel(2),
+            Message::PinChannel(4),
+            Message::PinChannel(DEFAULT_CHANNELS_COUNT),
+        ] {
+            let _ = app.update(message);
+        }
+        assert_eq!(app.pinned_channels, [4, 2]);
+
+        let _ = app.update(Message::UnpinChannel(4));
+        let _ = app.update(Message::UnpinChannel(7));
+        assert_eq!(app.pinned_channels, [2]);
+    }
+
+    #[test]
+    fn shrinking_the_channel_set_unpins_removed_channels() {
+        let mut app = test_app();
+        for index in [6, 1, 3] {
+            let _ = app.update(Message::PinChannel(index));
+        }
+        let _ = app.update(Message::ModifyingChannelsCount(4));
+        let _ = app.update(Message::ReleasedChannelsCountSlider);
+        assert_eq!(app.pinned_channels, [1, 3]);
+    }
+
+    #[test]
+    fn pinned_channels_survive_the_session() {
+        let temp_dir = TempDir::new("session");
+        let path = temp_dir.file("session.json");
+        let mut app = test_app();
+        for index in [5, 0] {
+            let _ = app.update(Message::PinChannel(index));
+        }
+        app.session_state().save(&path).unwrap();
+        let mut session = SessionState::load(&path).unwrap();
+        assert_eq!(session.pinned_channels, [6, 1]);
+
+        // Channels from a session with more channels, or pinned twice, are dropped
+        session
+            .pinned_channels
+            .extend([DEFAULT_CHANNELS_COUNT + 1, 6, 0]);
+        let mut restored_app = test_app();
+        restored_app.restore_session(session);
+        assert_eq!(restored_app.pinned_channels, [5, 0]);
     }
 }
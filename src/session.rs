@@ -0,0 +1,164 @@
+// Per-user session state of the retained-mode app, saved on exit and restored on startup.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::channel_metadata::ChannelMetadata;
+use crate::common::{ApplicationTab, APP_CONFIG_DIR_NAME};
+use crate::startup_args::startup_option;
+
+//*  Constants */
+/// Bump whenever `SessionState` changes in a way older files can't be read as.
+pub const SESSION_SCHEMA_VERSION: u32 = 1;
+pub const SESSION_FILE_FLAG: &str = "--session-file";
+pub const SESSION_FILE_ENV: &str = "CHANNEL_SESSION_FILE";
+pub const SESSION_FILE_NAME: &str = "session.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionState {
+    pub schema_version: u32,
+    pub suspicious_limit: f64,
+    /// Selected channels, numbered from 1 as in the UI.
+    pub previous_channel: Option<usize>,
+    pub current_channel: Option<usize>,
+    pub active_tab: ApplicationTab,
+    /// Channels pinned to the comparison table, numbered from 1.
+    #[serde(default)]
+    pub pinned_channels: Vec<usize>,
+    /// Metadata of every channel in order, missing in sessions saved before it existed.
+    #[serde(default)]
+    pub channel_metadata: Vec<ChannelMetadata>,
+}
+
+impl SessionState {
+    /// Reads the session from `path`, `None` if there is no file or it can't be used.
+    pub fn load(path: &Path) -> Option<Self> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return None,
+            Err(err) => {
+                eprintln!("Can't read session file {}: {err}", path.display());
+                return None;
+            }
+        };
+
+        // Check the version first so files from other versions are reported as such
+        // rather than as a parse error of some field
+        let document: serde_json::Value = match serde_json::from_str(&contents) {
+            Ok(document) => document,
+            Err(err) => {
+                eprintln!("Broken session file {}: {err}", path.display());
+                return None;
+            }
+        };
+        let schema_version = document.get("schema_version").and_then(|v| v.as_u64());
+        if schema_version != Some(SESSION_SCHEMA_VERSION as u64) {
+            eprintln!(
+                "Session file {} has schema version {:?}, expected {SESSION_SCHEMA_VERSION}",
+                path.display(),
+                schema_version
+            );
+            return None;
+        }
+
+        match serde_json::from_value(document) {
+            Ok(session) => Some(session),
+            Err(err) => {
+                eprintln!("Broken session file {}: {err}", path.display());
+                None
+            }
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Session file from `--session-file`/`CHANNEL_SESSION_FILE`, or the default one in the
+/// user's config directory.
+pub fn session_file_path() -> Option<PathBuf> {
+    startup_option(SESSION_FILE_FLAG, SESSION_FILE_ENV)
+        .map(PathBuf::from)
+        .or_else(|| {
+            dirs::config_dir().map(|dir| dir.join(APP_CONFIG_DIR_NAME).join(SESSION_FILE_NAME))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::TempDir;
+
+    fn session() -> SessionState {
+        SessionState {
+            schema_version: SESSION_SCHEMA_VERSION,
+            suspicious_limit: 42.0,
+            previous_channel: Some(2),
+            current_channel: None,
+            active_tab: ApplicationTab::Settings,
+            pinned_channels: vec![4, 1],
+            channel_metadata: vec![ChannelMetadata {
+                name: "Pressure".to_string(),
+                display_order: Some(3),
+                ..ChannelMetadata::default()
+            }],
+        }
+    }
+
+    fn load(contents: &str) -> Option<SessionState> {
+        let temp_dir = TempDir::new("session");
+        let path = temp_dir.file(SESSION_FILE_NAME);
+        fs::write(&path, contents).unwrap();
+        SessionState::load(&path)
+    }
+
+    #[test]
+    fn sessions_round_trip() {
+        let temp_dir = TempDir::new("session_round_trip");
+        let path = temp_dir.file("nested").join(SESSION_FILE_NAME);
+        session().save(&path).unwrap();
+
+        let loaded = SessionState::load(&path).unwrap();
+        assert_eq!(loaded.suspicious_limit, 42.0);
+        assert_eq!(loaded.previous_channel, Some(2));
+        assert_eq!(loaded.current_channel, None);
+        assert_eq!(loaded.active_tab, ApplicationTab::Settings);
+        assert_eq!(loaded.pinned_channels, [4, 1]);
+        assert_eq!(loaded.channel_metadata, session().channel_metadata);
+    }
+
+    #[test]
+    fn fields_added_later_default_when_missing() {
+        let loaded = load(
+            r#"{ "schema_version": 1, "suspicious_limit": 50.0, "previous_channel": null,
+                 "current_channel": 3, "active_tab": "Home" }"#,
+        )
+        .unwrap();
+        assert_eq!(loaded.current_channel, Some(3));
+        assert!(loaded.pinned_channels.is_empty());
+        assert!(loaded.channel_metadata.is_empty());
+    }
+
+    #[test]
+    fn unusable_sessions_are_ignored() {
+        let temp_dir = TempDir::new("missing_session");
+        assert!(SessionState::load(&temp_dir.file(SESSION_FILE_NAME)).is_none());
+
+        let mut document = serde_json::to_value(session()).unwrap();
+        document["schema_version"] = (SESSION_SCHEMA_VERSION + 1).into();
+        assert!(load(&document.to_string()).is_none());
+        document["schema_version"] = serde_json::Value::Null;
+        assert!(load(&document.to_string()).is_none());
+
+        assert!(load(r#"{ "schema_version": 1, "suspicious_limit": "high" }"#).is_none());
+        assert!(load("{ not json").is_none());
+    }
+}
@@ -0,0 +1,29 @@
+// Startup options shared by the app modules.
+// Every option can be given on the command line (`--flag value` or `--flag=value`)
+// or through an environment variable, the command line wins.
+
+#[cfg(feature = "retained-mode")]
+use std::str::FromStr;
+
+/// Raw value of a startup option, `None` if it's set neither on the command line nor in the environment.
+pub fn startup_option(flag: &str, env_var: &str) -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    std::env::var(env_var).ok()
+}
+
+/// Parsed value of a startup option, `None` if it's missing or can't be parsed.
+#[cfg(feature = "retained-mode")]
+pub fn parsed_startup_option<T: FromStr>(flag: &str, env_var: &str) -> Option<T> {
+    startup_option(flag, env_var).and_then(|value| value.trim().parse().ok())
+}
@@ -0,0 +1,495 @@
+// Rules deciding whether a channel value is suspicious.
+// Rules can be set for all channels or overridden for single channels, the first rule
+// that fires is reported back so the UI can explain why a channel was flagged.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+
+use crate::startup_args::startup_option;
+
+//*  Constants */
+pub const RULES_FLAG: &str = "--rules";
+pub const RULES_ENV: &str = "CHANNEL_RULES";
+pub const RULES_FILE_FLAG: &str = "--rules-file";
+pub const RULES_FILE_ENV: &str = "CHANNEL_RULES_FILE";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SuspiciousRule {
+    /// Above the limit selected with the slider, the original behaviour of the app.
+    AboveLimit,
+    UpperBound(f64),
+    LowerBound(f64),
+    /// Suspicious while the value is inside `low..=high`.
+    InBand {
+        low: f64,
+        high: f64,
+    },
+    /// Suspicious while the value is outside `low..=high`.
+    OutOfBand {
+        low: f64,
+        high: f64,
+    },
+    /// Fires above `trigger` and keeps firing until the value drops below `release`.
+    Hysteresis {
+        trigger: f64,
+        release: f64,
+    },
+    /// Fires when the value moved by more than `max_delta` since the previous sample.
+    RateOfChange {
+        max_delta: f64,
+    },
+    /// Fires when the last `count` samples were all above `limit`.
+    ConsecutiveOver {
+        limit: f64,
+        count: usize,
+    },
+}
+
+/// Everything a rule may look at when evaluating a single channel.
+pub struct RuleContext<'a> {
+    /// Samples of the channel, oldest first, the last one is the value being evaluated.
+    pub recent_values: &'a [f64],
+    pub current_limit: f64,
+    /// Rules that fired for this channel on the previous evaluation, including the ones
+    /// not reported because an earlier rule fired too.
+    pub previously_fired: &'a [SuspiciousRule],
+}
+
+impl SuspiciousRule {
+    pub fn fires(&self, context: &RuleContext) -> bool {
+        let Some(&value) = context.recent_values.last() else {
+            return false;
+        };
+
+        match *self {
+            SuspiciousRule::AboveLimit => value > context.current_limit,
+            SuspiciousRule::UpperBound(limit) => value > limit,
+            SuspiciousRule::LowerBound(limit) => value < limit,
+            SuspiciousRule::InBand { low, high } => (low..=high).contains(&value),
+            SuspiciousRule::OutOfBand { low, high } => !(low..=high).contains(&value),
+            SuspiciousRule::Hysteresis { trigger, release } => {
+                if context.previously_fired.contains(self) {
+                    value >= release
+                } else {
+                    value > trigger
+                }
+            }
+            SuspiciousRule::RateOfChange { max_delta } => {
+                let values = context.recent_values;
+                values.len() >= 2 && (values[values.len() - 2] - value).abs() > max_delta
+            }
+            SuspiciousRule::ConsecutiveOver { limit, count } => {
+                count > 0
+                    && context.recent_values.len() >= count
+                    && context.recent_values[context.recent_values.len() - count..]
+                        .iter()
+                        .all(|&sample| sample > limit)
+            }
+        }
+    }
+
+    /// Value the rule compares `value` against: the bound nearest to it for bands and the
+    /// largest allowed change for rates of change.
+    pub fn limit(&self, value: f64, current_limit: f64) -> f64 {
+        match *self {
+            SuspiciousRule::AboveLimit => current_limit,
+            SuspiciousRule::UpperBound(limit)
+            | SuspiciousRule::LowerBound(limit)
+            | SuspiciousRule::ConsecutiveOver { limit, .. } => limit,
+            SuspiciousRule::Hysteresis { trigger, .. } => trigger,
+            SuspiciousRule::InBand { low, high } | SuspiciousRule::OutOfBand { low, high } => {
+                if (value - low).abs() <= (value - high).abs() {
+                    low
+                } else {
+                    high
+                }
+            }
+            SuspiciousRule::RateOfChange { max_delta } => max_delta,
+        }
+    }
+
+    /// Describes the rule for the given limit, e.g. "> 75".
+    pub fn explain(&self, current_limit: f64) -> String {
+        match self {
+            SuspiciousRule::AboveLimit => format!("> {current_limit}"),
+            SuspiciousRule::UpperBound(limit) => format!("> {limit}"),
+            SuspiciousRule::LowerBound(limit) => format!("< {limit}"),
+            SuspiciousRule::InBand { low, high } => format!("in {low}..{high}"),
+            SuspiciousRule::OutOfBand { low, high } => format!("out of {low}..{high}"),
+            SuspiciousRule::Hysteresis { trigger, release } => {
+                format!("> {trigger} (until < {release})")
+            }
+            SuspiciousRule::RateOfChange { max_delta } => format!("changed by > {max_delta}"),
+            SuspiciousRule::ConsecutiveOver { limit, count } => {
+                format!("{count} samples > {limit}")
+            }
+        }
+    }
+}
+
+/// Global rules plus per-channel overrides.
+#[derive(Debug, Clone)]
+pub struct RuleSet {
+    pub global: Vec<SuspiciousRule>,
+    /// Rules for a single channel replace the global ones for that channel.
+    pub per_channel: HashMap<usize, Vec<SuspiciousRule>>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet {
+            global: vec![SuspiciousRule::AboveLimit],
+            per_channel: HashMap::new(),
+        }
+    }
+}
+
+impl RuleSet {
+    pub fn rules_for(&self, channel_index: usize) -> &[SuspiciousRule] {
+        self.per_channel.get(&channel_index).unwrap_or(&self.global)
+    }
+
+    /// Every rule that fires for the channel, in the order they were given.
+    /// The first one is what the channel is flagged for.
+    pub fn evaluate(&self, channel_index: usize, context: &RuleContext) -> Vec<SuspiciousRule> {
+        self.rules_for(channel_index)
+            .iter()
+            .filter(|rule| rule.fires(context))
+            .cloned()
+            .collect()
+    }
+
+    /// Parses rules separated by `;` or new lines, e.g. `upper 80; 3: out-of-band 10 90`.
+    ///
+    /// An optional 1-based channel number followed by `:` assigns the rule to that channel only,
+    /// empty entries and lines starting with `#` are skipped.
+    pub fn parse(spec: &str) -> Result<Self, RuleParseError> {
+        let mut global = Vec::new();
+        let mut per_channel: HashMap<usize, Vec<SuspiciousRule>> = HashMap::new();
+
+        for entry in spec.split([';', '\n']).map(str::trim) {
+            if entry.is_empty() || entry.starts_with('#') {
+                continue;
+            }
+
+            let (channel, rule_text) = match entry.split_once(':') {
+                Some((channel, rule_text)) => {
+                    let channel = channel
+                        .trim()
+                        .parse::<usize>()
+                        .ok()
+                        .filter(|&channel| channel > 0)
+                        .ok_or_else(|| RuleParseError::new(entry, "bad channel number"))?;
+                    (Some(channel - 1), rule_text)
+                }
+                None => (None, entry),
+            };
+
+            let rule =
+                parse_rule(rule_text).map_err(|reason| RuleParseError::new(entry, reason))?;
+            match channel {
+                Some(index) => per_channel.entry(index).or_default().push(rule),
+                None => global.push(rule),
+            }
+        }
+
+        if global.is_empty() {
+            global.push(SuspiciousRule::AboveLimit);
+        }
+
+        Ok(RuleSet {
+            global,
+            per_channel,
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct RuleParseError {
+    pub entry: String,
+    pub reason: &'static str,
+}
+
+impl RuleParseError {
+    fn new(entry: &str, reason: &'static str) -> Self {
+        RuleParseError {
+            entry: entry.to_string(),
+            reason,
+        }
+    }
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "'{}': {}", self.entry, self.reason)
+    }
+}
+
+impl std::error::Error for RuleParseError {}
+
+fn parse_rule(text: &str) -> Result<SuspiciousRule, &'static str> {
+    let mut words = text.split_whitespace();
+    let kind = words.next().ok_or("missing rule kind")?;
+    let numbers = words
+        .map(|word| word.parse::<f64>().ok().filter(|number| number.is_finite()))
+        .collect::<Option<Vec<_>>>()
+        .ok_or("rule arguments must be numbers")?;
+
+    let rule = match (kind, numbers.as_slice()) {
+        ("limit", []) => SuspiciousRule::AboveLimit,
+        ("upper", &[limit]) => SuspiciousRule::UpperBound(limit),
+        ("lower", &[limit]) => SuspiciousRule::LowerBound(limit),
+        ("in-band", &[low, high]) if low <= high => SuspiciousRule::InBand { low, high },
+        ("out-of-band", &[low, high]) if low <= high => SuspiciousRule::OutOfBand { low, high },
+        ("hysteresis", &[trigger, release]) if release <= trigger => {
+            SuspiciousRule::Hysteresis { trigger, release }
+        }
+        ("rate", &[max_delta]) => SuspiciousRule::RateOfChange { max_delta },
+        ("consecutive", &[limit, count]) if count >= 1.0 && count.fract() == 0.0 => {
+            SuspiciousRule::ConsecutiveOver {
+                limit,
+                count: count as usize,
+            }
+        }
+        (
+            "limit" | "upper" | "lower" | "in-band" | "out-of-band" | "hysteresis" | "rate"
+            | "consecutive",
+            _,
+        ) => return Err("wrong arguments for the rule"),
+        _ => return Err("unknown rule kind"),
+    };
+    Ok(rule)
+}
+
+/// Rules from `--rules`/`CHANNEL_RULES` or, failing that, `--rules-file`/`CHANNEL_RULES_FILE`.
+/// Falls back to the slider limit alone if nothing is set or the rules can't be parsed.
+pub fn from_startup_args() -> RuleSet {
+    let spec = match startup_option(RULES_FLAG, RULES_ENV) {
+        Some(spec) => spec,
+        None => match startup_option(RULES_FILE_FLAG, RULES_FILE_ENV) {
+            Some(path) => match fs::read_to_string(&path) {
+                Ok(spec) => spec,
+                Err(err) => {
+                    eprintln!("Can't read rules file '{path}': {err}. Using the default rule.");
+                    return RuleSet::default();
+                }
+            },
+            None => return RuleSet::default(),
+        },
+    };
+
+    RuleSet::parse(&spec).unwrap_or_else(|err| {
+        eprintln!("Invalid suspicious rule {err}. Using the default rule.");
+        RuleSet::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context<'a>(
+        recent_values: &'a [f64],
+        previously_fired: &'a [SuspiciousRule],
+    ) -> RuleContext<'a> {
+        RuleContext {
+            recent_values,
+            current_limit: 75.0,
+            previously_fired,
+        }
+    }
+
+    fn fires(rule: &SuspiciousRule, recent_values: &[f64]) -> bool {
+        rule.fires(&context(recent_values, &[]))
+    }
+
+    #[test]
+    fn rules_fire_on_their_conditions() {
+        assert!(fires(&SuspiciousRule::AboveLimit, &[76.0]));
+        assert!(!fires(&SuspiciousRule::AboveLimit, &[75.0]));
+        assert!(fires(&SuspiciousRule::UpperBound(10.0), &[10.5]));
+        assert!(!fires(&SuspiciousRule::UpperBound(10.0), &[10.0]));
+        assert!(fires(&SuspiciousRule::LowerBound(10.0), &[9.5]));
+        assert!(!fires(&SuspiciousRule::LowerBound(10.0), &[10.0]));
+
+        let in_band = SuspiciousRule::InBand {
+            low: 10.0,
+            high: 20.0,
+        };
+        let out_of_band = SuspiciousRule::OutOfBand {
+            low: 10.0,
+            high: 20.0,
+        };
+        for (value, inside) in [(9.0, false), (10.0, true), (20.0, true), (21.0, false)] {
+            assert_eq!(fires(&in_band, &[value]), inside, "{value}");
+            assert_eq!(fires(&out_of_band, &[value]), !inside, "{value}");
+        }
+
+        let rate = SuspiciousRule::RateOfChange { max_delta: 5.0 };
+        assert!(!fires(&rate, &[50.0]));
+        assert!(!fires(&rate, &[50.0, 55.0]));
+        assert!(fires(&rate, &[50.0, 44.0]));
+
+        let consecutive = SuspiciousRule::ConsecutiveOver {
+            limit: 50.0,
+            count: 3,
+        };
+        assert!(!fires(&consecutive, &[60.0, 60.0]));
+        assert!(!fires(&consecutive, &[60.0, 40.0, 60.0, 60.0]));
+        assert!(fires(&consecutive, &[40.0, 60.0, 60.0, 60.0]));
+
+        assert!(!fires(&SuspiciousRule::AboveLimit, &[]));
+    }
+
+    #[test]
+    fn hysteresis_holds_until_the_release_value() {
+        let rule = SuspiciousRule::Hysteresis {
+            trigger: 80.0,
+            release: 60.0,
+        };
+        let latched = [rule.clone()];
+        assert!(!rule.fires(&context(&[70.0], &[])));
+        assert!(rule.fires(&context(&[81.0], &[])));
+        assert!(rule.fires(&context(&[70.0], &latched)));
+        assert!(rule.fires(&context(&[60.0], &latched)));
+        assert!(!rule.fires(&context(&[59.0], &latched)));
+    }
+
+    #[test]
+    fn hysteresis_latches_while_an_earlier_rule_is_reported() {
+        let hysteresis = SuspiciousRule::Hysteresis {
+            trigger: 80.0,
+            release: 60.0,
+        };
+        let rules = RuleSet::parse("upper 50; hysteresis 80 60").unwrap();
+
+        let fired = rules.evaluate(0, &context(&[90.0], &[]));
+        assert_eq!(
+            fired,
+            [SuspiciousRule::UpperBound(50.0), hysteresis.clone()]
+        );
+        // Below the trigger, but the hysteresis still remembers it fired
+        let fired = rules.evaluate(0, &context(&[70.0], &fired));
+        assert_eq!(fired, [SuspiciousRule::UpperBound(50.0), hysteresis]);
+        let fired = rules.evaluate(0, &context(&[55.0], &fired));
+        assert_eq!(fired, [SuspiciousRule::UpperBound(50.0)]);
+    }
+
+    #[test]
+    fn limits_of_rules() {
+        assert_eq!(SuspiciousRule::AboveLimit.limit(90.0, 75.0), 75.0);
+        assert_eq!(SuspiciousRule::LowerBound(5.0).limit(1.0, 75.0), 5.0);
+        let hysteresis = SuspiciousRule::Hysteresis {
+            trigger: 80.0,
+            release: 60.0,
+        };
+        assert_eq!(hysteresis.limit(70.0, 75.0), 80.0);
+        let rate = SuspiciousRule::RateOfChange { max_delta: 5.0 };
+        assert_eq!(rate.limit(40.0, 75.0), 5.0);
+        let band = SuspiciousRule::OutOfBand {
+            low: 10.0,
+            high: 90.0,
+        };
+        assert_eq!(band.limit(5.0, 75.0), 10.0);
+        assert_eq!(band.limit(95.0, 75.0), 90.0);
+    }
+
+    #[test]
+    fn channel_rules_replace_the_global_ones() {
+        let rules = RuleSet::parse("upper 80\n# comment\n\n 3: lower 10; 3: limit").unwrap();
+        assert_eq!(rules.global, [SuspiciousRule::UpperBound(80.0)]);
+        assert_eq!(
+            rules.rules_for(2),
+            [SuspiciousRule::LowerBound(10.0), SuspiciousRule::AboveLimit]
+        );
+        assert_eq!(rules.rules_for(0), rules.global);
+
+        assert_eq!(
+            rules.evaluate(0, &context(&[90.0], &[])),
+            [SuspiciousRule::UpperBound(80.0)]
+        );
+        assert_eq!(
+            rules.evaluate(2, &context(&[78.0], &[])),
+            [SuspiciousRule::AboveLimit]
+        );
+        assert!(rules.evaluate(2, &context(&[50.0], &[])).is_empty());
+    }
+
+    #[test]
+    fn empty_rules_fall_back_to_the_limit() {
+        let rules = RuleSet::parse(" ; # nothing here").unwrap();
+        assert_eq!(rules.global, [SuspiciousRule::AboveLimit]);
+        assert!(rules.per_channel.is_empty());
+
+        let rules = RuleSet::parse("2: upper 5").unwrap();
+        assert_eq!(rules.global, [SuspiciousRule::AboveLimit]);
+    }
+
+    #[test]
+    fn every_rule_kind_parses() {
+        let rules = [
+            ("limit", SuspiciousRule::AboveLimit),
+            ("upper 80", SuspiciousRule::UpperBound(80.0)),
+            ("lower -5.5", SuspiciousRule::LowerBound(-5.5)),
+            (
+                "in-band 10 20",
+                SuspiciousRule::InBand {
+                    low: 10.0,
+                    high: 20.0,
+                },
+            ),
+            (
+                "out-of-band 10 20",
+                SuspiciousRule::OutOfBand {
+                    low: 10.0,
+                    high: 20.0,
+                },
+            ),
+            (
+                "hysteresis 80 60",
+                SuspiciousRule::Hysteresis {
+                    trigger: 80.0,
+                    release: 60.0,
+                },
+            ),
+            ("rate 5", SuspiciousRule::RateOfChange { max_delta: 5.0 }),
+            (
+                "consecutive 50 3",
+                SuspiciousRule::ConsecutiveOver {
+                    limit: 50.0,
+                    count: 3,
+                },
+            ),
+        ];
+        for (text, rule) in rules {
+            assert_eq!(parse_rule(text), Ok(rule), "{text}");
+        }
+    }
+
+    #[test]
+    fn bad_rules_are_reported() {
+        let bad_rules = [
+            ("", "missing rule kind"),
+            ("median 5", "unknown rule kind"),
+            ("upper high", "rule arguments must be numbers"),
+            ("upper inf", "rule arguments must be numbers"),
+            ("upper", "wrong arguments for the rule"),
+            ("limit 5", "wrong arguments for the rule"),
+            ("in-band 20 10", "wrong arguments for the rule"),
+            ("hysteresis 60 80", "wrong arguments for the rule"),
+            ("consecutive 50 2.5", "wrong arguments for the rule"),
+            ("consecutive 50 0", "wrong arguments for the rule"),
+        ];
+        for (text, reason) in bad_rules {
+            assert_eq!(parse_rule(text), Err(reason), "{text}");
+        }
+
+        let err = RuleSet::parse("upper 80; 0: lower 5").unwrap_err();
+        assert_eq!(err.to_string(), "'0: lower 5': bad channel number");
+        let err = RuleSet::parse("x: lower 5").unwrap_err();
+        assert_eq!(err.reason, "bad channel number");
+        let err = RuleSet::parse("2: median").unwrap_err();
+        assert_eq!(err.to_string(), "'2: median': unknown rule kind");
+    }
+}
@@ -0,0 +1,37 @@
+// Helpers shared by the unit tests of several modules.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT_TEMP_DIR: AtomicUsize = AtomicUsize::new(0);
+
+/// Directory of its own in the system temp directory, removed with everything in it when
+/// dropped, also when the test fails. Tests running in parallel never share one.
+pub struct TempDir {
+    path: PathBuf,
+}
+
+impl TempDir {
+    /// `name` ends up in the directory name to tell leftovers of a killed run apart.
+    pub fn new(name: &str) -> Self {
+        let number = NEXT_TEMP_DIR.fetch_add(1, Ordering::Relaxed);
+        let path = std::env::temp_dir().join(format!(
+            "gui_test_project_{}_{number}_{name}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&path).unwrap();
+        TempDir { path }
+    }
+
+    /// Path of `file_name` inside the directory, the file isn't created.
+    pub fn file(&self, file_name: &str) -> PathBuf {
+        self.path.join(file_name)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
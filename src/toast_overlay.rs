@@ -0,0 +1,277 @@
+// Toasts drawn on top of the retained-mode app's content.
+// iced 0.10 has no stacking widget, so `ToastOverlay` passes everything to the content and
+// only adds the toasts as an overlay in the bottom right corner of the window.
+
+use iced::advanced::layout::{self, Layout};
+use iced::advanced::overlay::{self, Overlay};
+use iced::advanced::widget::{self, Tree, Widget};
+use iced::advanced::{mouse, renderer, Clipboard, Shell};
+use iced::event::{self, Event};
+use iced::widget::{button, container, text, Column, Container, Row};
+use iced::{Background, Color, Element, Length, Point, Rectangle, Renderer, Size, Theme};
+
+use crate::app_theme::Palette;
+use crate::notifications::{Toast, TOAST_MARGIN, TOAST_WIDTH};
+use crate::retained_mode_app::rgb_color;
+
+/// Puts `toasts` over `content`, newest at the bottom. The "x" of a toast sends `on_dismiss`.
+pub fn view<'a, 'b, Message: Clone + 'a>(
+    content: impl Into<Element<'a, Message>>,
+    toasts: impl Iterator<Item = &'b Toast>,
+    palette: &Palette,
+    on_dismiss: fn(u64) -> Message,
+) -> Element<'a, Message> {
+    let mut toast_column = Column::new().spacing(10);
+    let mut has_toasts = false;
+    for toast in toasts {
+        has_toasts = true;
+        let row = Row::new()
+            .spacing(10)
+            .align_items(iced::Alignment::Center)
+            .push(text(toast.severity.to_string()).style(rgb_color(toast.severity.color(palette))))
+            .push(text(&toast.text).width(Length::Fill))
+            .push(
+                button(text("x"))
+                    .on_press(on_dismiss(toast.id))
+                    .style(iced::theme::Button::Text),
+            );
+        toast_column = toast_column.push(Container::new(row).padding(10).width(TOAST_WIDTH).style(
+            iced::theme::Container::Custom(Box::new(ToastStyle {
+                background: rgb_color(palette.background),
+                text: rgb_color(palette.text),
+                border: rgb_color(toast.severity.color(palette)),
+            })),
+        ));
+    }
+
+    Element::new(ToastOverlay {
+        content: content.into(),
+        toasts: toast_column.into(),
+        has_toasts,
+    })
+}
+
+// Card of a single toast, bordered in the colour of its severity
+struct ToastStyle {
+    background: Color,
+    text: Color,
+    border: Color,
+}
+
+impl container::StyleSheet for ToastStyle {
+    type Style = Theme;
+
+    fn appearance(&self, _style: &Theme) -> container::Appearance {
+        container::Appearance {
+            text_color: Some(self.text),
+            background: Some(Background::Color(self.background)),
+            border_radius: 6.0.into(),
+            border_width: 2.0,
+            border_color: self.border,
+        }
+    }
+}
+
+struct ToastOverlay<'a, Message> {
+    content: Element<'a, Message>,
+    toasts: Element<'a, Message>,
+    has_toasts: bool,
+}
+
+impl<'a, Message> Widget<Message, Renderer> for ToastOverlay<'a, Message> {
+    fn width(&self) -> Length {
+        self.content.as_widget().width()
+    }
+
+    fn height(&self) -> Length {
+        self.content.as_widget().height()
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        self.content.as_widget().layout(renderer, limits)
+    }
+
+    fn draw(
+        &self,
+        tree: &Tree,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+    ) {
+        self.content.as_widget().draw(
+            &tree.children[0],
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            viewport,
+        );
+    }
+
+    fn children(&self) -> Vec<Tree> {
+        vec![Tree::new(&self.content), Tree::new(&self.toasts)]
+    }
+
+    fn diff(&self, tree: &mut Tree) {
+        tree.diff_children(&[&self.content, &self.toasts]);
+    }
+
+    fn operate(
+        &self,
+        tree: &mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+        operation: &mut dyn widget::Operation<Message>,
+    ) {
+        self.content
+            .as_widget()
+            .operate(&mut tree.children[0], layout, renderer, operation);
+    }
+
+    fn on_event(
+        &mut self,
+        tree: &mut Tree,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+        viewport: &Rectangle,
+    ) -> event::Status {
+        self.content.as_widget_mut().on_event(
+            &mut tree.children[0],
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            viewport,
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        tree: &Tree,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.content.as_widget().mouse_interaction(
+            &tree.children[0],
+            layout,
+            cursor,
+            viewport,
+            renderer,
+        )
+    }
+
+    // Overlays of the content (e.g. open pick lists) stay below the toasts
+    fn overlay<'b>(
+        &'b mut self,
+        tree: &'b mut Tree,
+        layout: Layout<'_>,
+        renderer: &Renderer,
+    ) -> Option<overlay::Element<'b, Message, Renderer>> {
+        let [content_tree, toasts_tree] = &mut tree.children[..] else {
+            return None;
+        };
+        let content_overlay = self
+            .content
+            .as_widget_mut()
+            .overlay(content_tree, layout, renderer);
+        if !self.has_toasts {
+            return content_overlay;
+        }
+
+        let toast_layer = overlay::Element::new(
+            layout.position(),
+            Box::new(ToastLayer {
+                toasts: &mut self.toasts,
+                tree: toasts_tree,
+            }),
+        );
+        Some(
+            overlay::Group::with_children(
+                content_overlay.into_iter().chain([toast_layer]).collect(),
+            )
+            .overlay(),
+        )
+    }
+}
+
+struct ToastLayer<'a, 'b, Message> {
+    toasts: &'b mut Element<'a, Message>,
+    tree: &'b mut Tree,
+}
+
+impl<'a, 'b, Message> Overlay<Message, Renderer> for ToastLayer<'a, 'b, Message> {
+    fn layout(&self, renderer: &Renderer, bounds: Size, position: Point) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, bounds);
+        let mut node = self.toasts.as_widget().layout(renderer, &limits);
+        let size = node.size();
+        node.move_to(Point::new(
+            position.x + bounds.width - size.width - TOAST_MARGIN,
+            position.y + bounds.height - size.height - TOAST_MARGIN,
+        ));
+        node
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        theme: &Theme,
+        style: &renderer::Style,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+    ) {
+        self.toasts.as_widget().draw(
+            self.tree,
+            renderer,
+            theme,
+            style,
+            layout,
+            cursor,
+            &layout.bounds(),
+        );
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        renderer: &Renderer,
+        clipboard: &mut dyn Clipboard,
+        shell: &mut Shell<'_, Message>,
+    ) -> event::Status {
+        self.toasts.as_widget_mut().on_event(
+            self.tree,
+            event,
+            layout,
+            cursor,
+            renderer,
+            clipboard,
+            shell,
+            &layout.bounds(),
+        )
+    }
+
+    fn mouse_interaction(
+        &self,
+        layout: Layout<'_>,
+        cursor: mouse::Cursor,
+        viewport: &Rectangle,
+        renderer: &Renderer,
+    ) -> mouse::Interaction {
+        self.toasts
+            .as_widget()
+            .mouse_interaction(self.tree, layout, cursor, viewport, renderer)
+    }
+}
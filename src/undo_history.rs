@@ -0,0 +1,54 @@
+// Undo/redo stacks of state snapshots.
+// Callers decide what a snapshot contains and when a change is worth recording.
+
+//*  Constants */
+pub const MAX_UNDO_ENTRIES: usize = 100;
+
+#[derive(Debug)]
+pub struct UndoHistory<T> {
+    undo_stack: Vec<T>,
+    redo_stack: Vec<T>,
+}
+
+impl<T> Default for UndoHistory<T> {
+    fn default() -> Self {
+        UndoHistory {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+        }
+    }
+}
+
+impl<T> UndoHistory<T> {
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Remembers the state from before a change, a new change makes the redone states unreachable.
+    pub fn record(&mut self, before: T) {
+        self.undo_stack.push(before);
+        if self.undo_stack.len() > MAX_UNDO_ENTRIES {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.clear();
+    }
+
+    /// Returns the state to go back to. `current` takes a snapshot of the present state for
+    /// redo and gets the restored one, so it can capture the same parts of the state.
+    pub fn undo(&mut self, current: impl FnOnce(&T) -> T) -> Option<T> {
+        let previous = self.undo_stack.pop()?;
+        self.redo_stack.push(current(&previous));
+        Some(previous)
+    }
+
+    /// Returns the state to go forward to, `current` works as in `undo`.
+    pub fn redo(&mut self, current: impl FnOnce(&T) -> T) -> Option<T> {
+        let next = self.redo_stack.pop()?;
+        self.undo_stack.push(current(&next));
+        Some(next)
+    }
+}